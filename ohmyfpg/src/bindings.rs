@@ -1,14 +1,28 @@
-use futures::future::FutureExt;
+use chrono::{NaiveDate, NaiveDateTime};
 use ohmyfpg_core::client::{
-    self, ColumnResult, Connection, ConnectionError, FetchError, MessageReadError,
+    self, CancelToken, CancelTokens, ColumnResult, Connection, ConnectionError, CsvOptions,
+    CsvQuoteStyle, Cursor, ExportError, ExportFormat, FetchBatches, FetchError, FetchResult,
+    IsolationLevel, MessageReadError, ParquetCompression, ParquetOptions, PartitionOptions,
+    Pipeline, PreparedStatement, SimpleQueryResult, TransactionOptions,
 };
+use ohmyfpg_core::types::{ToSql, Value};
 use pyo3::conversion::IntoPy;
 use pyo3::create_exception;
-use pyo3::exceptions::{self, PyException, PyOSError};
+use pyo3::exceptions::{
+    PyException, PyOSError, PyRuntimeError, PyStopAsyncIteration, PyTimeoutError, PyTypeError,
+    PyValueError,
+};
 use pyo3::prelude::*;
+use pyo3::types::{
+    PyBool, PyBytes, PyDate, PyDateAccess, PyDateTime, PyFloat, PyLong, PyString, PyTimeAccess,
+};
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::fs::File;
+use tokio::io::BufWriter;
+use tokio::sync::{Mutex, OwnedMutexGuard};
 
 create_exception!(ohmyfpg, PyInvalidDsnError, PyException, "Invalid Dsn.");
 create_exception!(
@@ -24,45 +38,429 @@ create_exception!(
     "Unexpected message."
 );
 create_exception!(ohmyfpg, PyServerError, PyException, "Server error.");
+create_exception!(ohmyfpg, PyExportError, PyException, "Export error.");
+create_exception!(ohmyfpg, PyTlsError, PyException, "TLS error.");
+create_exception!(
+    ohmyfpg,
+    PyAuthenticationError,
+    PyException,
+    "Authentication error."
+);
 
 /// Connect to the database and return a `Connection` object.
-#[pyfunction]
-pub fn connect(py: Python, dsn: String) -> PyResult<&PyAny> {
-    pyo3_asyncio::tokio::future_into_py(
-        py,
-        client::connect(dsn).map(|res| {
-            res.map(PyConnection::from)
-                .map_err(|err| PyErr::from(LocalConnectionError(err)))
-        }),
-    )
+///
+/// The connection parameters of the DSN are overridden by `params`.
+#[pyfunction(
+    statement_cache_size = "client::DEFAULT_STATEMENT_CACHE_SIZE",
+    params = "vec![]"
+)]
+pub fn connect(
+    py: Python<'_>,
+    dsn: String,
+    statement_cache_size: usize,
+    params: Vec<(String, String)>,
+) -> PyResult<&PyAny> {
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let mut connection = client::connect_with_params(dsn, params)
+            .await
+            .map_err(|err| PyErr::from(LocalConnectionError(err)))?;
+        connection
+            .set_statement_cache_size(statement_cache_size)
+            .await
+            .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+        Ok(PyConnection::from(connection))
+    })
+}
+
+/// Fetch the result of the query split into `partitions` ranges of the `partition_on`
+/// column, each fetched concurrently with its own connection.
+#[pyfunction(bounds = "None")]
+pub fn fetch_partitioned(
+    py: Python<'_>,
+    dsn: String,
+    query_string: String,
+    partition_on: String,
+    partitions: usize,
+    bounds: Option<(i64, i64)>,
+) -> PyResult<PyQuery> {
+    let options = PartitionOptions::new(partition_on, partitions, bounds);
+    let cancel_tokens = CancelTokens::new();
+    PyQuery::new(py, cancel_tokens.clone(), |state| async move {
+        state.start()?;
+        client::fetch_partitioned_with_cancel_tokens(dsn, query_string, options, cancel_tokens)
+            .await
+            .map(|fr| Python::with_gil(|py| LocalFetchResult(fr).into_py(py)))
+            .map_err(|err| PyErr::from(LocalConnectionError(err)))
+    })
 }
 
 /// Connection object exposed to Python
 #[pyclass(name = "Connection")]
 pub struct PyConnection {
     wrappee: Arc<Mutex<Connection>>,
+    /// Kept out of the mutex, which is held by the query to cancel.
+    cancel_token: Option<CancelToken>,
 }
 
 #[pymethods]
 impl PyConnection {
-    fn fetch<'a>(&self, py: Python<'a>, query_string: String) -> PyResult<&'a PyAny> {
+    #[args(params = "None")]
+    fn fetch<'a>(
+        &self,
+        py: Python<'a>,
+        query_string: String,
+        params: Option<Vec<LocalValue>>,
+    ) -> PyResult<PyQuery> {
         let mutext_conn = Arc::clone(&self.wrappee);
+        let params = params.unwrap_or_default();
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let params: Vec<&dyn ToSql> = params.iter().map(|p| &p.0 as &dyn ToSql).collect();
+            state
+                .lock(&mutext_conn)
+                .await?
+                .fetch(query_string, &params)
+                .await
+                .map(|fr| Python::with_gil(|py| LocalFetchResult(fr).into_py(py)))
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    /// Cancel the query being run, if any, without waiting for the connection to be
+    /// released.
+    fn cancel<'a>(&self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let cancel_token = self.cancel_token.clone().ok_or_else(|| {
+            PyRuntimeError::new_err("the server doesn't support cancelling queries")
+        })?;
         pyo3_asyncio::tokio::future_into_py(py, async move {
-            mutext_conn
-                .lock()
-                .await
-                .fetch(query_string)
-                .await
-                .map(|fr| {
-                    Python::with_gil(|py| {
-                        let local_fr: HashMap<String, LocalColumnResult> = fr
-                            .into_iter()
-                            .map(|v| (v.0, LocalColumnResult(v.1)))
-                            .collect();
-                        local_fr.into_py(py)
-                    })
-                })
-                .map_err(|_| exceptions::PyException::new_err("TODO"))
+            cancel_token
+                .cancel()
+                .await
+                .map_err(|err| PyErr::from(LocalConnectionError(err)))
+        })
+    }
+
+    #[args(isolation_level = "None", read_only = "false", deferrable = "false")]
+    fn begin<'a>(
+        &self,
+        py: Python<'a>,
+        isolation_level: Option<String>,
+        read_only: bool,
+        deferrable: bool,
+    ) -> PyResult<PyQuery> {
+        let isolation_level = isolation_level
+            .map(|il| il.parse::<IsolationLevel>())
+            .transpose()
+            .map_err(PyValueError::new_err)?;
+        let options = TransactionOptions::new(isolation_level, read_only, deferrable);
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .begin(&options)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    fn commit(&self, py: Python) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .commit()
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    fn rollback(&self, py: Python) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .rollback()
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    fn savepoint(&self, py: Python) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .savepoint()
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    fn release_savepoint(&self, py: Python, name: String) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .release_savepoint(&name)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    fn rollback_to_savepoint(&self, py: Python, name: String) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .rollback_to_savepoint(&name)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    fn fetch_pipeline<'a>(
+        &self,
+        py: Python<'a>,
+        queries: Vec<(String, Vec<LocalValue>)>,
+    ) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        let mut pipeline = Pipeline::new();
+        for (query_string, params) in queries.into_iter() {
+            let params: Vec<&dyn ToSql> = params.iter().map(|p| &p.0 as &dyn ToSql).collect();
+            pipeline.push(query_string, &params);
+        }
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let results = state
+                .lock(&mutext_conn)
+                .await?
+                .fetch_pipeline(pipeline)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            Ok(Python::with_gil(|py| {
+                results
+                    .into_iter()
+                    .map(|fr| LocalFetchResult(fr).into_py(py))
+                    .collect::<Vec<PyObject>>()
+                    .into_py(py)
+            }))
+        })
+    }
+
+    fn fetch_copy(&self, py: Python<'_>, query_string: String) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .fetch_copy(query_string)
+                .await
+                .map(|fr| Python::with_gil(|py| LocalFetchResult(fr).into_py(py)))
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    #[args(params = "None")]
+    fn execute<'a>(
+        &self,
+        py: Python<'a>,
+        query_string: String,
+        params: Option<Vec<LocalValue>>,
+    ) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        let params = params.unwrap_or_default();
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let params: Vec<&dyn ToSql> = params.iter().map(|p| &p.0 as &dyn ToSql).collect();
+            let tag = state
+                .lock(&mutext_conn)
+                .await?
+                .execute(query_string, &params)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            Ok((tag.command, tag.rows))
+        })
+    }
+
+    /// Columns are given as tuples of name, big-endian values and `numpy` dtype.
+    fn copy_from_arrays<'a>(
+        &self,
+        py: Python<'a>,
+        table: String,
+        columns: Vec<(String, &PyBytes, String)>,
+    ) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        let columns: Vec<(String, ColumnResult)> = columns
+            .into_iter()
+            .map(|(name, bytes, dtype)| (name, ColumnResult::new(bytes.as_bytes().to_vec(), dtype)))
+            .collect();
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .copy_in_columns(&table, &columns)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))
+        })
+    }
+
+    fn simple_query(&self, py: Python<'_>, query_string: String) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let results = state
+                .lock(&mutext_conn)
+                .await?
+                .simple_query(query_string)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            Ok(Python::with_gil(|py| {
+                results
+                    .into_iter()
+                    .map(|res| LocalSimpleQueryResult(res).into_py(py))
+                    .collect::<Vec<PyObject>>()
+            }))
+        })
+    }
+
+    fn prepare(&self, py: Python<'_>, query_string: String) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        let cancel_token = self.cancel_token.clone();
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let mut conn = state.lock(&mutext_conn).await?;
+            let stmt = conn
+                .prepare(query_string)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            let type_name = |oid| conn.pg_type(oid).map(|t| t.name.to_owned());
+            let param_types = stmt.param_types().iter().map(|oid| type_name(*oid));
+            let columns = stmt
+                .desc()
+                .fields
+                .iter()
+                .map(|field| (field.name.to_owned(), type_name(field.data_type_oid)));
+            let stmt = PyPreparedStatement {
+                connection: Arc::clone(&mutext_conn),
+                cancel_token,
+                param_types: param_types.collect(),
+                columns: columns.collect(),
+                wrappee: Arc::new(Mutex::new(Some(stmt))),
+            };
+            Ok(stmt)
+        })
+    }
+
+    fn cursor(&self, py: Python<'_>, query_string: String) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        let cancel_token = self.cancel_token.clone();
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let cursor = state
+                .lock(&mutext_conn)
+                .await?
+                .open_cursor(query_string)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            Ok(PyCursor {
+                connection: mutext_conn,
+                cancel_token,
+                wrappee: Arc::new(Mutex::new(Some(cursor))),
+            })
+        })
+    }
+
+    fn fetch_batches(&self, query_string: String, batch_rows: usize) -> PyFetchBatches {
+        PyFetchBatches {
+            connection: Arc::clone(&self.wrappee),
+            query_string,
+            batch_rows,
+            state: Arc::new(Mutex::new(BatchesState::Pending)),
+        }
+    }
+
+    #[args(row_group_size = "1048576", compression = "\"snappy\"")]
+    fn fetch_to_parquet<'a>(
+        &self,
+        py: Python<'a>,
+        query_string: String,
+        path: String,
+        row_group_size: usize,
+        compression: &str,
+    ) -> PyResult<PyQuery> {
+        let compression = compression
+            .parse::<ParquetCompression>()
+            .map_err(PyValueError::new_err)?;
+        let options = ParquetOptions::new(row_group_size, compression);
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            state
+                .lock(&mutext_conn)
+                .await?
+                .fetch_to_parquet(query_string, path, options)
+                .await
+                .map_err(|err| PyErr::from(LocalExportError(err)))
+        })
+    }
+
+    #[args(
+        delimiter = "\",\"",
+        quoting = "\"necessary\"",
+        header = "true",
+        null = "String::new()"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn fetch_to_csv<'a>(
+        &self,
+        py: Python<'a>,
+        query_string: String,
+        path: String,
+        delimiter: &str,
+        quoting: &str,
+        header: bool,
+        null: String,
+    ) -> PyResult<PyQuery> {
+        let delimiter = match delimiter.as_bytes() {
+            [delimiter] => *delimiter,
+            _ => {
+                return Err(PyValueError::new_err(
+                    "The delimiter must be a single byte character",
+                ))
+            }
+        };
+        let quote_style = quoting
+            .parse::<CsvQuoteStyle>()
+            .map_err(PyValueError::new_err)?;
+        let format = ExportFormat::Csv(CsvOptions::new(delimiter, quote_style, header, null));
+        self.export(py, query_string, path, format)
+    }
+
+    fn fetch_to_ndjson(
+        &self,
+        py: Python<'_>,
+        query_string: String,
+        path: String,
+    ) -> PyResult<PyQuery> {
+        self.export(py, query_string, path, ExportFormat::Ndjson)
+    }
+}
+
+impl PyConnection {
+    fn export(
+        &self,
+        py: Python<'_>,
+        query_string: String,
+        path: String,
+        format: ExportFormat,
+    ) -> PyResult<PyQuery> {
+        let mutext_conn = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let mut conn = state.lock(&mutext_conn).await?;
+            let res = async {
+                let mut writer = BufWriter::new(File::create(path).await?);
+                conn.export(query_string, &mut writer, format).await
+            };
+            res.await.map_err(|err| PyErr::from(LocalExportError(err)))
         })
     }
 }
@@ -70,11 +468,351 @@ impl PyConnection {
 impl From<Connection> for PyConnection {
     fn from(conn: Connection) -> Self {
         PyConnection {
+            cancel_token: conn.cancel_token(),
             wrappee: Arc::new(Mutex::new(conn)),
         }
     }
 }
 
+const QUERY_WAITING: u8 = 0;
+const QUERY_RUNNING: u8 = 1;
+const QUERY_CANCELLED: u8 = 2;
+const QUERY_DONE: u8 = 3;
+
+/// Whether a query is waiting for the connection, running, cancelled before it
+/// started, or done. Shared between its future and the `Query` returned to Python.
+#[derive(Clone, Default)]
+struct QueryState(Arc<AtomicU8>);
+
+impl QueryState {
+    /// Locks the connection to run the query, unless it was cancelled while waiting.
+    async fn lock(
+        &self,
+        connection: &Arc<Mutex<Connection>>,
+    ) -> PyResult<OwnedMutexGuard<Connection>> {
+        let conn = Arc::clone(connection).lock_owned().await;
+        self.start()?;
+        Ok(conn)
+    }
+
+    /// Marks the query as running, unless it was cancelled while waiting.
+    fn start(&self) -> PyResult<()> {
+        self.0
+            .compare_exchange(
+                QUERY_WAITING,
+                QUERY_RUNNING,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .map_err(|_| PyRuntimeError::new_err("The query was cancelled before it started"))?;
+        Ok(())
+    }
+}
+
+/// Awaitable result of a query exposed to Python, which can be cancelled
+///
+/// Cancelling it only affects this query: it's not run at all if it's still waiting
+/// for the connection, e.g. held by another task.
+#[pyclass(name = "Query")]
+pub struct PyQuery {
+    fut: PyObject,
+    state: QueryState,
+    cancel: QueryCancel,
+}
+
+/// How a running query is cancelled: with the token of its connection, or with the
+/// tokens of all the connections of `fetch_partitioned`.
+#[derive(Clone)]
+enum QueryCancel {
+    Connection(Option<CancelToken>),
+    Partitions(CancelTokens),
+}
+
+impl From<Option<CancelToken>> for QueryCancel {
+    fn from(cancel_token: Option<CancelToken>) -> Self {
+        QueryCancel::Connection(cancel_token)
+    }
+}
+
+impl From<CancelTokens> for QueryCancel {
+    fn from(cancel_tokens: CancelTokens) -> Self {
+        QueryCancel::Partitions(cancel_tokens)
+    }
+}
+
+impl PyQuery {
+    /// Runs the query of `f`, which locks the connection with `QueryState::lock`, or
+    /// marks the query as running with `QueryState::start` when it has its own
+    /// connections.
+    fn new<F, Fut, T>(py: Python<'_>, cancel: impl Into<QueryCancel>, f: F) -> PyResult<Self>
+    where
+        F: FnOnce(QueryState) -> Fut,
+        Fut: Future<Output = PyResult<T>> + Send + 'static,
+        T: IntoPy<PyObject>,
+    {
+        let state = QueryState::default();
+        let query = f(state.clone());
+        let query_state = state.clone();
+        let fut = pyo3_asyncio::tokio::future_into_py(py, async move {
+            let res = query.await;
+            query_state.0.store(QUERY_DONE, Ordering::SeqCst);
+            res
+        })?;
+        Ok(PyQuery {
+            fut: fut.into(),
+            state,
+            cancel: cancel.into(),
+        })
+    }
+}
+
+#[pymethods]
+impl PyQuery {
+    fn __await__(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.fut.call_method0(py, "__await__")
+    }
+
+    /// Cancel the query, server-side if it's running.
+    fn cancel<'a>(&self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let state = self.state.clone();
+        let cancel = self.cancel.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let running = state.0.compare_exchange(
+                QUERY_WAITING,
+                QUERY_CANCELLED,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) == Err(QUERY_RUNNING);
+            let res = match cancel {
+                QueryCancel::Connection(Some(cancel_token)) if running => {
+                    cancel_token.cancel().await
+                }
+                QueryCancel::Partitions(cancel_tokens) if running => cancel_tokens.cancel().await,
+                _ => Ok(()),
+            };
+            res.map_err(|err| PyErr::from(LocalConnectionError(err)))
+        })
+    }
+}
+
+/// Server-side cursor exposed to Python
+#[pyclass(name = "Cursor")]
+pub struct PyCursor {
+    connection: Arc<Mutex<Connection>>,
+    cancel_token: Option<CancelToken>,
+    // `None` once the cursor has been closed
+    wrappee: Arc<Mutex<Option<Cursor>>>,
+}
+
+#[pymethods]
+impl PyCursor {
+    fn fetch(&self, py: Python<'_>, max_rows: u32) -> PyResult<PyQuery> {
+        let connection = Arc::clone(&self.connection);
+        let cursor = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let mut cursor = cursor.lock().await;
+            let cursor = cursor
+                .as_mut()
+                .ok_or_else(|| PyValueError::new_err("The cursor is closed"))?;
+            let batch = state
+                .lock(&connection)
+                .await?
+                .fetch_cursor(cursor, max_rows)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            Ok(Python::with_gil(|py| {
+                batch.map(|fr| LocalFetchResult(fr).into_py(py))
+            }))
+        })
+    }
+
+    fn close(&self, py: Python<'_>) -> PyResult<PyQuery> {
+        let connection = Arc::clone(&self.connection);
+        let cursor = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let mut cursor = cursor.lock().await;
+            if cursor.is_none() {
+                return Ok(());
+            }
+            // Taken once the connection is locked, so that the cursor stays open if the
+            // close is cancelled while waiting for it
+            let mut conn = state.lock(&connection).await?;
+            if let Some(cursor) = cursor.take() {
+                conn.close_cursor(cursor)
+                    .await
+                    .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Prepared statement exposed to Python
+#[pyclass(name = "PreparedStatement")]
+pub struct PyPreparedStatement {
+    connection: Arc<Mutex<Connection>>,
+    cancel_token: Option<CancelToken>,
+    /// Names of the parameters types, `None` for the unsupported ones
+    #[pyo3(get)]
+    param_types: Vec<Option<String>>,
+    /// Names and types names of the result columns, `None` for the unsupported types
+    #[pyo3(get)]
+    columns: Vec<(String, Option<String>)>,
+    // `None` once the statement has been closed
+    wrappee: Arc<Mutex<Option<PreparedStatement>>>,
+}
+
+#[pymethods]
+impl PyPreparedStatement {
+    #[args(params = "None")]
+    fn fetch(&self, py: Python<'_>, params: Option<Vec<LocalValue>>) -> PyResult<PyQuery> {
+        let connection = Arc::clone(&self.connection);
+        let stmt = Arc::clone(&self.wrappee);
+        let params = params.unwrap_or_default();
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let stmt = stmt.lock().await;
+            let stmt = stmt
+                .as_ref()
+                .ok_or_else(|| PyValueError::new_err("The prepared statement is closed"))?;
+            if params.len() != stmt.param_types().len() {
+                return Err(PyValueError::new_err(format!(
+                    "Expected {} parameters, got {}",
+                    stmt.param_types().len(),
+                    params.len()
+                )));
+            }
+            // Python values are converted to the exact types of the parameters, e.g. an
+            // `int` is sent as `int4` rather than `int8` if that's what's expected
+            let params = params
+                .into_iter()
+                .zip(stmt.param_types())
+                .enumerate()
+                .map(|(i, (param, type_oid))| {
+                    param.0.clone().cast(*type_oid).ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "Parameter ${} can't be converted to type OID {}: {:?}",
+                            i + 1,
+                            type_oid,
+                            param.0
+                        ))
+                    })
+                })
+                .collect::<PyResult<Vec<Value>>>()?;
+            let params: Vec<&dyn ToSql> = params.iter().map(|p| p as &dyn ToSql).collect();
+            let res = state
+                .lock(&connection)
+                .await?
+                .fetch_prepared(stmt, &params)
+                .await
+                .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            Ok(Python::with_gil(|py| LocalFetchResult(res).into_py(py)))
+        })
+    }
+
+    fn close(&self, py: Python<'_>) -> PyResult<PyQuery> {
+        let connection = Arc::clone(&self.connection);
+        let stmt = Arc::clone(&self.wrappee);
+        PyQuery::new(py, self.cancel_token.clone(), |state| async move {
+            let mut stmt = stmt.lock().await;
+            if stmt.is_none() {
+                return Ok(());
+            }
+            // Taken once the connection is locked, so that the statement stays prepared
+            // if the close is cancelled while waiting for it
+            let mut conn = state.lock(&connection).await?;
+            if let Some(stmt) = stmt.take() {
+                conn.close_prepared(stmt)
+                    .await
+                    .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Async iterator over the batches of a query result exposed to Python
+///
+/// The connection is locked from the first batch until the last one has been read.
+#[pyclass(name = "FetchBatches")]
+pub struct PyFetchBatches {
+    connection: Arc<Mutex<Connection>>,
+    query_string: String,
+    batch_rows: usize,
+    state: Arc<Mutex<BatchesState>>,
+}
+
+enum BatchesState {
+    Pending,
+    Started(OwnedMutexGuard<Connection>, FetchBatches),
+    // The connection is released as soon as all the batches have been read
+    Done,
+}
+
+#[pymethods]
+impl PyFetchBatches {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let connection = Arc::clone(&self.connection);
+        let query_string = self.query_string.to_owned();
+        let batch_rows = self.batch_rows;
+        let state = Arc::clone(&self.state);
+        let fut = pyo3_asyncio::tokio::future_into_py(py, async move {
+            let mut state = state.lock().await;
+            if let BatchesState::Pending = *state {
+                let mut conn = connection.lock_owned().await;
+                let batches = conn
+                    .start_fetch_batches(query_string, batch_rows)
+                    .await
+                    .map_err(|err| PyErr::from(LocalFetchError(err)))?;
+                *state = BatchesState::Started(conn, batches);
+            }
+
+            let batch = match &mut *state {
+                BatchesState::Started(conn, batches) => conn.next_batch(batches).await,
+                _ => Ok(None),
+            };
+            match batch {
+                Ok(Some(fr)) => Ok(Python::with_gil(|py| LocalFetchResult(fr).into_py(py))),
+                Ok(None) => {
+                    *state = BatchesState::Done;
+                    Err(PyStopAsyncIteration::new_err(()))
+                }
+                Err(err) => {
+                    *state = BatchesState::Done;
+                    Err(PyErr::from(LocalFetchError(err)))
+                }
+            }
+        })?;
+        Ok(Some(fut.into()))
+    }
+
+    /// Releases the connection without reading the remaining batches, whose rows are
+    /// skipped when running the next query.
+    fn aclose<'a>(&self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let state = Arc::clone(&self.state);
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            *state.lock().await = BatchesState::Done;
+            Ok(())
+        })
+    }
+}
+
+struct LocalFetchResult(FetchResult);
+
+impl IntoPy<Py<PyAny>> for LocalFetchResult {
+    fn into_py(self, py: Python<'_>) -> Py<PyAny> {
+        let local_fr: HashMap<String, LocalColumnResult> = self
+            .0
+            .into_iter()
+            .map(|v| (v.0, LocalColumnResult(v.1)))
+            .collect();
+        local_fr.into_py(py)
+    }
+}
+
 struct LocalColumnResult(ColumnResult);
 
 impl IntoPy<Py<PyAny>> for LocalColumnResult {
@@ -87,25 +825,155 @@ impl IntoPy<Py<PyAny>> for LocalColumnResult {
     }
 }
 
+struct LocalSimpleQueryResult(SimpleQueryResult);
+
+impl IntoPy<Py<PyAny>> for LocalSimpleQueryResult {
+    fn into_py(self, py: Python<'_>) -> Py<PyAny> {
+        let res = self.0;
+        let columns: Option<Vec<String>> = res
+            .desc
+            .map(|desc| desc.fields.into_iter().map(|f| f.name).collect());
+        let (command, row_count) = match res.tag {
+            Some(tag) => (Some(tag.command), tag.rows),
+            None => (None, None),
+        };
+        (columns, res.rows, command, row_count).into_py(py)
+    }
+}
+
+struct LocalValue(Value);
+
+impl<'source> FromPyObject<'source> for LocalValue {
+    fn extract(obj: &'source PyAny) -> PyResult<Self> {
+        let value = if obj.is_none() {
+            Value::Null
+        // `bool` is a subclass of `int`, hence it must be checked first
+        } else if obj.is_instance_of::<PyBool>()? {
+            Value::Bool(obj.extract()?)
+        } else if obj.is_instance_of::<PyLong>()? {
+            Value::Int8(obj.extract()?)
+        } else if obj.is_instance_of::<PyFloat>()? {
+            Value::Float8(obj.extract()?)
+        } else if obj.is_instance_of::<PyString>()? {
+            Value::Text(obj.extract()?)
+        } else if obj.is_instance_of::<PyBytes>()? {
+            Value::Bytea(obj.extract()?)
+        // `datetime` is a subclass of `date`, hence it must be checked first
+        } else if let Ok(datetime) = obj.downcast::<PyDateTime>() {
+            if datetime.getattr("tzinfo")?.is_none() {
+                Value::Timestamp(to_naive_datetime(datetime)?)
+            } else {
+                let utc = obj
+                    .py()
+                    .import("datetime")?
+                    .getattr("timezone")?
+                    .getattr("utc")?;
+                let obj = obj.call_method1("astimezone", (utc,))?;
+                Value::TimestampTz(to_naive_datetime(obj.downcast()?)?)
+            }
+        } else if let Ok(date) = obj.downcast::<PyDate>() {
+            Value::Date(to_naive_date(date)?)
+        } else {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported parameter type: {}",
+                obj.get_type().name()?
+            )));
+        };
+        Ok(LocalValue(value))
+    }
+}
+
+fn to_naive_date(date: &PyDate) -> PyResult<NaiveDate> {
+    NaiveDate::from_ymd_opt(
+        date.get_year(),
+        date.get_month().into(),
+        date.get_day().into(),
+    )
+    .ok_or_else(|| PyValueError::new_err("Invalid date"))
+}
+
+fn to_naive_datetime(datetime: &PyDateTime) -> PyResult<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(
+        datetime.get_year(),
+        datetime.get_month().into(),
+        datetime.get_day().into(),
+    )
+    .and_then(|date| {
+        date.and_hms_micro_opt(
+            datetime.get_hour().into(),
+            datetime.get_minute().into(),
+            datetime.get_second().into(),
+            datetime.get_microsecond(),
+        )
+    })
+    .ok_or_else(|| PyValueError::new_err("Invalid datetime"))
+}
+
 struct LocalConnectionError(ConnectionError);
 
 impl From<LocalConnectionError> for PyErr {
     fn from(local_err: LocalConnectionError) -> Self {
+        let err = local_err.0;
+        let msg = err.to_string();
+        match err {
+            ConnectionError::InvalidDsnError(_) => PyInvalidDsnError::new_err(msg),
+            ConnectionError::FetchError(err) => PyErr::from(LocalFetchError(err)),
+            // Class 28 is "Invalid Authorization Specification", e.g. a wrong password
+            ConnectionError::ServerError(err) if err.code.starts_with("28") => {
+                PyAuthenticationError::new_err(msg)
+            }
+            ConnectionError::ServerError(_) => PyServerError::new_err(msg),
+            ConnectionError::TlsError(_) => PyTlsError::new_err(msg),
+            ConnectionError::ChannelBindingError(_) | ConnectionError::SASLAuthError(_) => {
+                PyAuthenticationError::new_err(msg)
+            }
+            ConnectionError::TargetSessionAttrsError(_) => PyOSError::new_err(msg),
+            ConnectionError::ConnectTimeoutError(_) => PyTimeoutError::new_err(msg),
+            // Raised as the error of the last host tried, with the errors of all of them
+            ConnectionError::HostsError(mut errors) => match errors.pop() {
+                Some((_, last_err)) => {
+                    let last_err = PyErr::from(LocalConnectionError(last_err));
+                    Python::with_gil(|py| PyErr::from_type(last_err.get_type(py), msg))
+                }
+                None => PyOSError::new_err(msg),
+            },
+        }
+    }
+}
+
+struct LocalFetchError(FetchError);
+
+impl From<LocalFetchError> for PyErr {
+    fn from(local_err: LocalFetchError) -> Self {
         let err = local_err.0;
         match err {
-            ConnectionError::InvalidDsnError(err) => PyInvalidDsnError::new_err(err.to_string()),
-            ConnectionError::FetchError(err) => match err {
-                FetchError::MessageReadError(err) => match err {
-                    MessageReadError::UnrecognizedMessageError(err) => {
-                        PyUnrecognizedMessageError::new_err(err.to_string())
-                    }
-                    MessageReadError::IOError(err) => PyOSError::new_err(err.to_string()),
-                },
-                FetchError::UnexpectedMessageError(msg) => {
-                    PyUnexpectedMessageError::new_err(format!("{:?}", msg))
+            FetchError::MessageReadError(err) => match err {
+                MessageReadError::UnrecognizedMessageError(err) => {
+                    PyUnrecognizedMessageError::new_err(err.to_string())
                 }
+                MessageReadError::IOError(err) => PyOSError::new_err(err.to_string()),
+                MessageReadError::QueryTimeoutError(_) => PyTimeoutError::new_err(err.to_string()),
             },
-            ConnectionError::ServerError(err) => PyServerError::new_err(err.to_string()),
+            FetchError::UnexpectedMessageError(msg) => {
+                PyUnexpectedMessageError::new_err(format!("{:?}", msg))
+            }
+            FetchError::InvalidParametersError(msg) => PyValueError::new_err(msg),
+            FetchError::InvalidStateError(msg) => PyRuntimeError::new_err(msg),
+            FetchError::UnsupportedTypeError(msg) => PyTypeError::new_err(msg),
+            FetchError::NullValueError(msg) => PyValueError::new_err(msg),
+        }
+    }
+}
+
+struct LocalExportError(ExportError);
+
+impl From<LocalExportError> for PyErr {
+    fn from(local_err: LocalExportError) -> Self {
+        let err = local_err.0;
+        match err {
+            ExportError::FetchError(err) => PyErr::from(LocalFetchError(err)),
+            ExportError::IOError(err) => PyOSError::new_err(err.to_string()),
+            ExportError::ParquetError(err) => PyExportError::new_err(err.to_string()),
         }
     }
 }
@@ -5,10 +5,21 @@ pub mod bindings;
 #[pymodule]
 fn ohmyfpg(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<bindings::PyConnection>()?;
+    m.add_class::<bindings::PyCursor>()?;
+    m.add_class::<bindings::PyFetchBatches>()?;
+    m.add_class::<bindings::PyPreparedStatement>()?;
+    m.add_class::<bindings::PyQuery>()?;
     m.add(
         "PyInvalidDSNError",
         py.get_type::<bindings::PyInvalidDsnError>(),
     )?;
+    m.add("PyExportError", py.get_type::<bindings::PyExportError>())?;
+    m.add("PyTlsError", py.get_type::<bindings::PyTlsError>())?;
+    m.add(
+        "PyAuthenticationError",
+        py.get_type::<bindings::PyAuthenticationError>(),
+    )?;
     m.add_function(wrap_pyfunction!(bindings::connect, m)?)?;
+    m.add_function(wrap_pyfunction!(bindings::fetch_partitioned, m)?)?;
     Ok(())
 }
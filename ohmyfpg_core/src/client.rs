@@ -1,23 +1,76 @@
-use crate::messages::authentication::sasl_authenticate;
+use crate::messages::authentication::{
+    md5_password, password_authenticate, sasl_authenticate, unexpected_message_error,
+};
 use crate::messages::query::{
-    Bind, DataRow, Describe, Execute, Flush, Format, Parse, RowDescription, Sync,
+    Bind, Close, DataRow, Describe, Execute, Flush, Format, Parse, Query, RowDescription, Sync,
+    Target,
 };
 use crate::messages::startup::StartupMessage;
 use crate::messages::DeserializeMessage;
 use crate::messages::{
-    BackendMessage, RawBackendMessage, RawTypedBackendMessage, SerializeMessage,
+    BackendMessage, RawBackendMessage, RawTypedBackendMessage, SerializeMessage, TransactionStatus,
 };
 use crate::server::PgType;
+use crate::types::ToSql;
 use rayon::prelude::*;
+use socket2::{SockRef, TcpKeepalive};
+mod batches;
+mod cancel;
+mod config;
+mod copy;
+mod cursor;
+#[cfg(feature = "polars")]
+mod dataframe;
 mod dsn;
-use std::collections::HashMap;
+mod execute;
+mod export;
+#[cfg(test)]
+mod mock_server;
+#[cfg(feature = "parquet")]
+mod parquet_export;
+mod partitioned;
+mod pipeline;
+mod prepared;
+mod simple_query;
+mod statement_cache;
+#[cfg(test)]
+mod tests;
+mod tls;
+mod transaction;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::{fmt, str};
 use tokio::io;
 use tokio::net::TcpStream;
+use tokio::time::{self, Duration, Instant};
 pub mod error;
 mod framer;
-pub use error::{ConnectionError, FetchError, MessageReadError, ServerError};
+pub use batches::FetchBatches;
+pub use cancel::{CancelToken, CancelTokens};
+use config::{Config, Host, KeepaliveOptions, LoadBalanceHosts, TargetSessionAttrs};
+pub use cursor::Cursor;
+#[cfg(feature = "polars")]
+pub use dataframe::to_dataframe;
+#[cfg(feature = "polars")]
+pub use error::FetchPolarsError;
+pub use error::{
+    ConnectionError, ExportError, FetchError, MessageReadError, SASLAuthError, ServerError,
+    TlsError,
+};
+pub use execute::CommandTag;
+pub use export::{CsvOptions, CsvQuoteStyle, ExportFormat};
 use framer::Framer;
+pub use framer::Stream;
+#[cfg(feature = "parquet")]
+pub use parquet_export::{ParquetCompression, ParquetOptions};
+pub use partitioned::{fetch_partitioned, fetch_partitioned_with_cancel_tokens, PartitionOptions};
+pub use pipeline::Pipeline;
+pub use prepared::PreparedStatement;
+pub use simple_query::SimpleQueryResult;
+use statement_cache::StatementCache;
+pub use statement_cache::DEFAULT_STATEMENT_CACHE_SIZE;
+pub use tls::{ChannelBinding, SslMode, TlsOptions};
+pub use transaction::{IsolationLevel, Transaction, TransactionOptions};
 
 pub type FetchResult = HashMap<String, ColumnResult>;
 
@@ -31,35 +84,254 @@ impl ColumnResult {
     pub fn new(bytes: Vec<u8>, dtype: String) -> Self {
         Self { bytes, dtype }
     }
+
+    /// Decodes the big-endian bytes of the column into native values.
+    pub fn to_native_vec<T, const N: usize>(&self, from_be_bytes: fn([u8; N]) -> T) -> Vec<T> {
+        self.bytes
+            .chunks_exact(N)
+            .map(|chunk| from_be_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
 }
 
 pub struct Connection {
     framer: Framer,
     pg_types: Option<HashMap<u32, PgType>>,
+    /// Portals of the cursors opened and neither closed nor dropped yet.
+    open_cursors: HashSet<String>,
+    /// Portals of the cursors dropped without being closed, pushed by `Cursor::drop`.
+    dropped_cursors: Arc<Mutex<Vec<String>>>,
+    next_portal_id: u32,
+    statement_cache: StatementCache,
+    transaction_status: TransactionStatus,
+    next_savepoint_id: u32,
+    /// Whether the transaction the cursors live in was started by `open_cursor`.
+    cursors_transaction: bool,
+    /// Statements rolling back the transactions dropped without being finished, sent
+    /// before the next message.
+    pending_rollbacks: Vec<String>,
+    /// Number of `ReadyForQuery` whose responses are skipped, i.e. of the pending
+    /// rollbacks and of the requests that timed out.
+    unread_responses: usize,
+    /// Number of `ReadyForQuery` the server has yet to send.
+    pending_responses: usize,
+    /// Whether extended query messages were sent since the last `Sync`.
+    unsynced: bool,
+    /// Whether the request that timed out must be ended with a `Sync`, so that its
+    /// response ends with a `ReadyForQuery` too.
+    needs_sync: bool,
+    query_timeout: Option<Duration>,
+    /// When the response of the last request times out.
+    deadline: Option<Instant>,
+    /// Id of the batches started by `start_fetch_batches` and not read entirely yet,
+    /// whose remaining rows are skipped if another request is sent.
+    unfinished_batches: Option<u32>,
+    next_batches_id: u32,
+    /// Set from the `BackendKeyData` sent by the server at startup.
+    cancel_token: Option<CancelToken>,
 }
 
 impl Connection {
-    pub fn new(stream: TcpStream) -> Self {
+    pub fn new(stream: Box<dyn Stream>) -> Self {
         Connection {
             framer: Framer::new(stream),
             pg_types: None,
+            open_cursors: HashSet::new(),
+            dropped_cursors: Arc::new(Mutex::new(vec![])),
+            next_portal_id: 0,
+            statement_cache: StatementCache::new(DEFAULT_STATEMENT_CACHE_SIZE),
+            transaction_status: TransactionStatus::Idle,
+            next_savepoint_id: 0,
+            cursors_transaction: false,
+            pending_rollbacks: vec![],
+            unread_responses: 0,
+            pending_responses: 0,
+            unsynced: false,
+            needs_sync: false,
+            query_timeout: None,
+            deadline: None,
+            unfinished_batches: None,
+            next_batches_id: 0,
+            cancel_token: None,
         }
     }
 
+    /// Returns the type with the given OID, if it's one of the supported ones.
+    pub fn pg_type(&self, oid: u32) -> Option<&PgType> {
+        self.pg_types
+            .as_ref()
+            .and_then(|pg_types| pg_types.get(&oid))
+    }
+
+    /// Status of the current transaction, as of the last query.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.transaction_status
+    }
+
+    /// Returns a token to cancel the queries of the connection from another task, if
+    /// the server sent the key data needed to do so.
+    pub fn cancel_token(&self) -> Option<CancelToken> {
+        self.cancel_token.clone()
+    }
+
+    /// Sets the maximum time to wait for the response of each request, counted from
+    /// when it's written, after which the query is cancelled, the rest of the response
+    /// is skipped and `MessageReadError::QueryTimeoutError` is returned.
+    ///
+    /// The whole response is bounded, including the time spent between reads, e.g. to
+    /// process the batches of a stream.
+    pub fn set_query_timeout(&mut self, query_timeout: Option<Duration>) {
+        self.query_timeout = query_timeout;
+    }
+
     pub async fn write_message<T>(&mut self, msg: T) -> io::Result<()>
     where
         T: SerializeMessage + fmt::Debug,
     {
+        self.write_pending_rollbacks().await?;
+        self.write_frame(msg).await
+    }
+
+    async fn write_frame<T>(&mut self, msg: T) -> io::Result<()>
+    where
+        T: SerializeMessage + fmt::Debug,
+    {
+        match msg.get_msg_type() {
+            // Both end with a `ReadyForQuery`
+            Some(b"S" | b"Q") => {
+                self.pending_responses += 1;
+                self.unsynced = false;
+            }
+            Some(b"P" | b"B" | b"D" | b"E" | b"C" | b"H") => self.unsynced = true,
+            _ => {}
+        }
+        self.start_query_timeout();
         self.framer.write_frame(msg).await
     }
 
-    async fn read_raw_message(&mut self) -> io::Result<RawBackendMessage> {
-        Ok(self.framer.read_frame().await.unwrap())
+    fn start_query_timeout(&mut self) {
+        self.deadline = self
+            .query_timeout
+            .map(|query_timeout| Instant::now() + query_timeout);
+    }
+
+    /// Skips the rest of the response of the current request when reading the next
+    /// message, e.g. after it timed out.
+    fn skip_current_response(&mut self) {
+        self.needs_sync = self.unsynced;
+        self.unread_responses = self.pending_responses + self.needs_sync as usize;
+        self.deadline = None;
+    }
+
+    /// Forgets the cursors dropped without being closed. Their portals are closed by
+    /// the server at the end of the transaction, which is rolled back if it was
+    /// started for the cursors and none is left.
+    fn forget_dropped_cursors(&mut self) {
+        let dropped_cursors = match self.dropped_cursors.lock() {
+            Ok(mut dropped_cursors) => std::mem::take(&mut *dropped_cursors),
+            Err(_) => return,
+        };
+        let mut forgotten = false;
+        for portal_name in dropped_cursors.iter() {
+            forgotten |= self.open_cursors.remove(portal_name);
+        }
+        if forgotten && self.open_cursors.is_empty() && self.cursors_transaction {
+            self.cursors_transaction = false;
+            self.pending_rollbacks.push("ROLLBACK".to_owned());
+        }
+    }
+
+    /// Sends the rollbacks of the transactions that were dropped without being
+    /// finished, whose responses are skipped when reading the next message.
+    ///
+    /// The request that timed out, or whose batches were abandoned, is ended first,
+    /// if needed.
+    async fn write_pending_rollbacks(&mut self) -> io::Result<()> {
+        self.forget_dropped_cursors();
+        if self.unfinished_batches.take().is_some() {
+            self.skip_current_response();
+        }
+        if self.needs_sync {
+            self.needs_sync = false;
+            self.write_frame(Sync::default()).await?;
+        }
+        if self.pending_rollbacks.is_empty() {
+            return Ok(());
+        }
+        let query_string = self.pending_rollbacks.join("; ");
+        self.pending_rollbacks.clear();
+        self.unread_responses += 1;
+        self.write_frame(Query::new(query_string)).await
+    }
+
+    async fn read_raw_message(&mut self) -> Result<RawBackendMessage, MessageReadError> {
+        let frame = match self.deadline {
+            Some(deadline) => {
+                // Checked first too, as reading the rest of a response already received
+                // doesn't wait, e.g. for a slow consumer of batches
+                let frame = if Instant::now() < deadline {
+                    time::timeout_at(deadline, self.framer.read_frame())
+                        .await
+                        .ok()
+                } else {
+                    None
+                };
+                match frame {
+                    Some(frame) => frame?,
+                    None => {
+                        // The responses still to come are skipped when reading the next one
+                        self.skip_current_response();
+                        // Otherwise the query keeps running, and the next ones wait for it.
+                        // Errors are ignored, as the response is skipped anyway.
+                        if let Some(cancel_token) = &self.cancel_token {
+                            let _ = cancel_token.cancel().await;
+                            // Ends the cancelled request right away, so that the server
+                            // is ready for the next one
+                            self.write_pending_rollbacks().await?;
+                            self.framer.flush().await?;
+                        }
+                        return Err(MessageReadError::QueryTimeoutError(
+                            self.query_timeout.unwrap_or_default(),
+                        ));
+                    }
+                }
+            }
+            None => self.framer.read_frame().await?,
+        };
+        let message = frame.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "connection closed by the server",
+            )
+        })?;
+        Ok(message)
     }
 
     async fn read_raw_typed_message(&mut self) -> Result<RawTypedBackendMessage, MessageReadError> {
-        let raw_message = self.read_raw_message().await?;
-        raw_message.identify().map_err(MessageReadError::from)
+        self.skip_unread_responses().await?;
+        let message = self.read_raw_message().await?.identify()?;
+        if let RawTypedBackendMessage::ReadyForQuery(body) = &message {
+            self.transaction_status = TransactionStatus::from_byte(body[0]);
+            self.pending_responses = self.pending_responses.saturating_sub(1);
+            if self.pending_responses == 0 {
+                self.deadline = None;
+            }
+        }
+        Ok(message)
+    }
+
+    /// Skips the responses of the rollbacks sent by `write_pending_rollbacks`, and of
+    /// the requests that timed out.
+    async fn skip_unread_responses(&mut self) -> Result<(), MessageReadError> {
+        while self.unread_responses > 0 {
+            let message = self.read_raw_message().await?.identify()?;
+            if let RawTypedBackendMessage::ReadyForQuery(body) = message {
+                self.transaction_status = TransactionStatus::from_byte(body[0]);
+                self.pending_responses = self.pending_responses.saturating_sub(1);
+                self.unread_responses -= 1;
+            }
+        }
+        Ok(())
     }
 
     pub async fn read_message(&mut self) -> Result<BackendMessage, MessageReadError> {
@@ -67,81 +339,234 @@ impl Connection {
         Ok(raw_typed_message.parse())
     }
 
+    /// Binds the parameters to the statement prepared for the query and executes it.
+    ///
+    /// Statements are prepared once and cached by query text, so that executing the
+    /// same query again skips parsing and describing it. The least recently used
+    /// statement is closed when the cache is full.
     async fn execute_prepared_query(
         &mut self,
         query_string: String,
+        params: &[&dyn ToSql],
     ) -> Result<RowDescription, FetchError> {
-        self.write_message(Parse::new(query_string.to_owned()))
-            .await?;
-        self.write_message(Bind::new(Format::Binary)).await?;
-        self.write_message(Describe::default()).await?;
+        let param_types: Vec<u32> = params.iter().map(|p| p.type_oid()).collect();
+        let param_values = params.iter().map(|p| p.to_sql()).collect();
+
+        let cached = self
+            .statement_cache
+            .get(&query_string, &param_types)
+            .map(|stmt| (stmt.name.to_owned(), stmt.desc.clone()));
+        let (statement_name, mut row_desc, evicted) = match cached {
+            Some((statement_name, row_desc)) => (statement_name, Some(row_desc), vec![]),
+            None => {
+                let (statement_name, evicted) = if self.statement_cache.is_enabled() {
+                    let evicted = self.statement_cache.make_room(&query_string);
+                    (self.statement_cache.next_name(), evicted)
+                } else {
+                    ("".to_owned(), vec![])
+                };
+                for name in evicted.iter() {
+                    self.write_message(Close::new(Target::Statement(name.to_owned())))
+                        .await?;
+                }
+                self.write_message(Parse::new_with_statement(
+                    statement_name.to_owned(),
+                    query_string.to_owned(),
+                    param_types.to_owned(),
+                ))
+                .await?;
+                self.write_message(Describe::new(Target::Statement(statement_name.to_owned())))
+                    .await?;
+                (statement_name, None, evicted)
+            }
+        };
+        self.write_message(Bind::new_with_statement(
+            "".to_owned(),
+            statement_name.to_owned(),
+            Format::Binary,
+            param_values,
+        ))
+        .await?;
         self.write_message(Execute::default()).await?;
         self.write_message(Flush::default()).await?;
 
-        let _message = self.read_message().await?;
-        // TODO: Ensure it's a `ParseComplete`
-        let _message = self.read_message().await?;
-        // TODO: Ensure it's a `BindComplete`
-        let message = self.read_message().await?;
+        let cache_hit = row_desc.is_some();
+        let res = async {
+            for _ in evicted.iter() {
+                match self.read_message().await? {
+                    BackendMessage::CloseComplete(_) => {}
+                    msg => return Err(FetchError::UnexpectedMessageError(msg)),
+                }
+            }
+            if row_desc.is_none() {
+                match self.read_message().await? {
+                    BackendMessage::ParseComplete(_) => {}
+                    msg => return Err(FetchError::UnexpectedMessageError(msg)),
+                }
+                match self.read_message().await? {
+                    BackendMessage::ParameterDescription(_) => {}
+                    msg => return Err(FetchError::UnexpectedMessageError(msg)),
+                }
+                let desc = match self.read_message().await? {
+                    BackendMessage::RowDescription(desc) => desc,
+                    // Statements that don't return rows have no columns
+                    BackendMessage::NoData(_) => RowDescription::new(vec![]),
+                    msg => return Err(FetchError::UnexpectedMessageError(msg)),
+                };
+                if !statement_name.is_empty() {
+                    self.statement_cache.insert(
+                        query_string.to_owned(),
+                        statement_name,
+                        param_types,
+                        desc.clone(),
+                    );
+                }
+                row_desc = Some(desc);
+            }
+            match self.read_message().await? {
+                BackendMessage::BindComplete(_) => Ok(row_desc.unwrap()),
+                msg => Err(FetchError::UnexpectedMessageError(msg)),
+            }
+        }
+        .await;
+
+        if let Err(FetchError::UnexpectedMessageError(_)) = res {
+            // The server skips every message until the next `Sync` after an error
+            self.sync().await?;
+            // The cached statement may be stale, e.g. after its tables were altered
+            if let Some(stmt) = cache_hit
+                .then(|| self.statement_cache.remove(&query_string))
+                .flatten()
+            {
+                self.write_message(Close::new(Target::Statement(stmt.name)))
+                    .await?;
+                self.sync().await?;
+            }
+        }
+        res
+    }
+
+    /// Changes the maximum number of prepared statements kept by the connection,
+    /// closing the least recently used ones that no longer fit. `0` disables caching.
+    pub async fn set_statement_cache_size(&mut self, size: usize) -> Result<(), FetchError> {
+        let evicted = self.statement_cache.resize(size);
+        if evicted.is_empty() {
+            return Ok(());
+        }
+        for name in evicted.into_iter() {
+            self.write_message(Close::new(Target::Statement(name)))
+                .await?;
+        }
+        self.sync().await
+    }
+
+    /// Runs a statement that doesn't return rows using the simple query protocol.
+    async fn run_command(&mut self, query_string: String) -> Result<(), FetchError> {
+        self.simple_query(query_string).await.map(|_| ())
+    }
 
-        match message {
-            BackendMessage::RowDescription(row_desc) => Ok(row_desc),
-            msg => Err(FetchError::UnexpectedMessageError(msg)),
+    /// Sends a `Sync` and skips every message until the server is ready for the next
+    /// query.
+    async fn sync(&mut self) -> Result<(), FetchError> {
+        self.write_message(Sync::default()).await?;
+        loop {
+            if let BackendMessage::ReadyForQuery(_) = self.read_message().await? {
+                return Ok(());
+            }
         }
     }
 
     pub async fn fetch_raw(
         &mut self,
         query_string: String,
+        params: &[&dyn ToSql],
     ) -> Result<(RowDescription, Vec<Vec<u8>>), FetchError> {
-        let row_desc = self.execute_prepared_query(query_string).await?;
+        let row_desc = self.execute_prepared_query(query_string, params).await?;
+        let (data_rows, _) = self.read_data_rows(usize::MAX).await?;
+        Ok((row_desc, data_rows))
+    }
+
+    /// Reads up to `max_rows` data rows of the query being executed. The returned flag
+    /// tells whether all the rows have been read and the connection is ready for the
+    /// next query.
+    async fn read_data_rows(
+        &mut self,
+        max_rows: usize,
+    ) -> Result<(Vec<Vec<u8>>, bool), FetchError> {
         let mut data_rows = vec![];
-        let mut error: Option<FetchError> = None;
-        loop {
+        while data_rows.len() < max_rows {
             let message = self.read_raw_typed_message().await?;
             match message {
                 RawTypedBackendMessage::DataRow(body) => data_rows.push(body),
                 RawTypedBackendMessage::ReadyForQuery(_) => {
-                    break;
+                    return Ok((data_rows, true));
                 }
                 RawTypedBackendMessage::CommandComplete(_) => {
                     self.write_message(Sync::default()).await?;
                 }
+                // Sent asynchronously, e.g. on `RAISE NOTICE` or `SET`
+                RawTypedBackendMessage::NoticeResponse(_)
+                | RawTypedBackendMessage::ParameterStatus(_) => {}
                 msg => {
-                    error = Some(FetchError::UnexpectedMessageError(msg.parse()));
-                    break;
+                    // The server skips every message until the next `Sync` after an error
+                    self.sync().await?;
+                    return Err(FetchError::UnexpectedMessageError(msg.parse()));
                 }
             };
         }
 
-        match error {
-            Some(err) => Err(err),
-            None => Ok((row_desc, data_rows)),
-        }
+        Ok((data_rows, false))
     }
 
-    pub async fn fetch(&mut self, query_string: String) -> Result<FetchResult, FetchError> {
-        let (desc, data_rows_bytes) = self.fetch_raw(query_string).await?;
+    /// Fetches the result of the query as columns. Parameters are referenced in the
+    /// query as `$1`, `$2`, etc.
+    pub async fn fetch(
+        &mut self,
+        query_string: String,
+        params: &[&dyn ToSql],
+    ) -> Result<FetchResult, FetchError> {
+        let (desc, data_rows_bytes) = self.fetch_raw(query_string, params).await?;
+        Ok(self
+            .decode_columns(&desc, data_rows_bytes)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Decodes the raw data rows into columns, preserving the order of the fields
+    /// in the row description.
+    ///
+    /// Only numeric columns can be decoded, `FetchError::UnsupportedTypeError` is
+    /// returned for the others, and `FetchError::NullValueError` for NULL values.
+    fn decode_columns(
+        &self,
+        desc: &RowDescription,
+        data_rows_bytes: Vec<Vec<u8>>,
+    ) -> Result<Vec<(String, ColumnResult)>, FetchError> {
         let total_rows = data_rows_bytes.len();
         let mut cols_meta = vec![];
         let mut index_field_map = HashMap::new();
-        for (i, field) in desc.fields.into_iter().enumerate() {
-            let field_name = field.name;
-            let pg_type = self
-                .pg_types
-                .as_ref()
-                .unwrap()
-                .get(&field.data_type_oid)
-                .unwrap();
+        for (i, field) in desc.fields.iter().enumerate() {
+            let field_name = &field.name;
+            let pg_type = self.pg_type(field.data_type_oid).ok_or_else(|| {
+                FetchError::UnsupportedTypeError(format!(
+                    "column \"{}\" has type OID {}",
+                    field_name, field.data_type_oid
+                ))
+            })?;
             let pg_type_name = pg_type.name.as_str();
             let dtype_prefix = match pg_type_name {
                 "int2" | "int4" | "int8" => "i".to_owned(),
                 "float4" | "float8" => "f".to_owned(),
-                _ => todo!("{}", format!("Unsopported pg_type: {pg_type_name}")),
+                _ => {
+                    return Err(FetchError::UnsupportedTypeError(format!(
+                        "column \"{}\" has type {}",
+                        field_name, pg_type_name
+                    )))
+                }
             };
             let dtype = format!(">{}{}", dtype_prefix, pg_type.size.unwrap());
 
-            cols_meta.push((field_name.to_owned(), pg_type_name, dtype));
+            cols_meta.push((field_name.to_owned(), dtype));
 
             index_field_map.insert(i, field_name.to_owned());
         }
@@ -149,84 +574,316 @@ impl Connection {
         let chunks = data_rows_bytes
             .into_par_iter()
             .map(DataRow::deserialize_body)
-            .fold(
+            .try_fold(
                 HashMap::new,
                 |mut acc: HashMap<&str, Vec<u8>>, dr: DataRow| {
                     for (i, c) in dr.columns.into_iter().enumerate() {
-                        // TODO: handle `null`s
-                        let raw_bytes = &c.unwrap();
-
-                        let value = match cols_meta[i].1 {
-                            "int2" | "int4" | "int8" | "float4" | "float8" => raw_bytes.to_vec(),
-                            _ => todo!("{}", format!("Unsopported pg_type: {}", cols_meta[i].1)),
-                        };
-
                         let field_name = index_field_map.get(&i).unwrap();
+                        // The columns are decoded into arrays without a validity mask
+                        let value = c.ok_or_else(|| {
+                            FetchError::NullValueError(format!(
+                                "column \"{}\" has a NULL value",
+                                field_name
+                            ))
+                        })?;
+
                         let entry = acc.entry(field_name);
-                        entry.or_default().extend(value);
+                        entry.or_default().extend_from_slice(&value);
                     }
-                    acc
+                    Ok(acc)
                 },
             )
-            .collect::<Vec<HashMap<&str, Vec<u8>>>>();
+            .collect::<Result<Vec<HashMap<&str, Vec<u8>>>, FetchError>>()?;
 
-        let mut fr = FetchResult::new();
+        let mut columns = Vec::with_capacity(cols_meta.len());
         for col_meta in cols_meta {
             let field_name = col_meta.0;
-            let dtype = &col_meta.2;
+            let dtype = &col_meta.1;
             let mut col_res = ColumnResult::new(Vec::with_capacity(total_rows), dtype.to_string());
 
             for chunk in &chunks {
-                let value = chunk.get(&field_name as &str).unwrap();
-                col_res.bytes.extend_from_slice(value);
+                if let Some(value) = chunk.get(&field_name as &str) {
+                    col_res.bytes.extend_from_slice(value);
+                }
             }
 
-            fr.insert(field_name.to_owned(), col_res);
+            columns.push((field_name, col_res));
+        }
+        Ok(columns)
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn set_keepalive(stream: &TcpStream, keepalives: &KeepaliveOptions) -> io::Result<()> {
+    let socket = SockRef::from(stream);
+    if !keepalives.enabled {
+        return socket.set_keepalive(false);
+    }
+    let mut keepalive = TcpKeepalive::new();
+    if let Some(idle) = keepalives.idle {
+        keepalive = keepalive.with_time(idle);
+    }
+    if let Some(interval) = keepalives.interval {
+        keepalive = keepalive.with_interval(interval);
+    }
+    // Like libpq, not supported on Windows
+    #[cfg(not(windows))]
+    if let Some(count) = keepalives.count {
+        keepalive = keepalive.with_retries(count);
+    }
+    socket.set_tcp_keepalive(&keepalive)
+}
+
+/// Fisher-Yates shuffle, for `load_balance_hosts=random`.
+fn shuffle<T>(items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        let mut bytes = [0u8; 4];
+        getrandom::getrandom(&mut bytes).unwrap();
+        let j = u32::from_le_bytes(bytes) as usize % (i + 1);
+        items.swap(i, j);
+    }
+}
+
+/// Checks the session against the `target_session_attrs`, using the parameters
+/// reported by servers since Postgres 14, or else querying them.
+async fn check_target_session_attrs(
+    connection: &mut Connection,
+    server_params: &HashMap<String, String>,
+    target_session_attrs: TargetSessionAttrs,
+) -> Result<(), ConnectionError> {
+    if target_session_attrs == TargetSessionAttrs::Any {
+        return Ok(());
+    }
+    let in_hot_standby = match server_params.get("in_hot_standby") {
+        Some(value) => value == "on",
+        None => query_value(connection, "SELECT pg_catalog.pg_is_in_recovery()").await? == "t",
+    };
+    let read_only = in_hot_standby
+        || match server_params.get("default_transaction_read_only") {
+            Some(value) => value == "on",
+            None => query_value(connection, "SHOW transaction_read_only").await? == "on",
+        };
+    let mismatch = match target_session_attrs {
+        TargetSessionAttrs::ReadWrite if read_only => Some("session is read-only"),
+        TargetSessionAttrs::ReadOnly if !read_only => Some("session is not read-only"),
+        TargetSessionAttrs::Primary if in_hot_standby => Some("server is in hot standby mode"),
+        TargetSessionAttrs::Standby if !in_hot_standby => Some("server is not in hot standby mode"),
+        _ => None,
+    };
+    match mismatch {
+        Some(msg) => Err(ConnectionError::TargetSessionAttrsError(msg.to_owned())),
+        None => Ok(()),
+    }
+}
+
+/// Opens a stream to the host, over TLS if required by the options. The server
+/// certificate is returned along with the stream when using TLS.
+async fn open_stream(
+    host: &Host,
+    tls_options: &TlsOptions,
+    keepalives: &KeepaliveOptions,
+) -> Result<(Box<dyn Stream>, Option<Vec<u8>>), ConnectionError> {
+    match host.unix_socket() {
+        // Like libpq, TLS isn't used over Unix domain sockets
+        Some(path) => Ok((connect_unix_socket(&path).await?, None)),
+        None => {
+            let stream = connect_tcp(host, keepalives).await?;
+            match tls::negotiate(stream, &host.host, tls_options).await {
+                // Like libpq, retried without TLS when it's only preferred, over a new
+                // connection as the failed handshake consumed this one
+                Err(ConnectionError::TlsError(TlsError::HandshakeError(_)))
+                    if tls_options.mode == SslMode::Prefer =>
+                {
+                    Ok((Box::new(connect_tcp(host, keepalives).await?), None))
+                }
+                res => res,
+            }
         }
-        Ok(fr)
     }
 }
 
+async fn connect_tcp(host: &Host, keepalives: &KeepaliveOptions) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect((host.host.as_str(), host.port)).await?;
+    set_keepalive(&stream, keepalives)?;
+    Ok(stream)
+}
+
+/// Returns the first value of the query, which must return one.
+async fn query_value(connection: &mut Connection, query: &str) -> Result<String, FetchError> {
+    connection
+        .simple_query(query.to_owned())
+        .await?
+        .into_iter()
+        .next()
+        .and_then(|result| result.rows.into_iter().next())
+        .and_then(|row| row.into_iter().next().flatten())
+        .ok_or_else(|| FetchError::InvalidStateError(format!("No value returned by: {}", query)))
+}
+
+#[cfg(unix)]
+async fn connect_unix_socket(path: &std::path::Path) -> Result<Box<dyn Stream>, ConnectionError> {
+    Ok(Box::new(tokio::net::UnixStream::connect(path).await?))
+}
+
+#[cfg(not(unix))]
+async fn connect_unix_socket(_path: &std::path::Path) -> Result<Box<dyn Stream>, ConnectionError> {
+    Err(ConnectionError::from(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Unix domain sockets are not supported on this platform",
+    )))
+}
+
 pub async fn connect(raw_dsn: String) -> Result<Connection, ConnectionError> {
-    let parsed_dsn = dsn::parse_dsn(&raw_dsn)?;
-    let address = parsed_dsn.address;
-    println!("Connecting to {}...", address);
-    let stream = TcpStream::connect(address).await?;
+    connect_with_params(raw_dsn, vec![]).await
+}
+
+/// Same as `connect`, with the connection parameters of the DSN overridden by
+/// `params`, e.g. `("connect_timeout", "10")`.
+pub async fn connect_with_params(
+    raw_dsn: String,
+    params: Vec<(String, String)>,
+) -> Result<Connection, ConnectionError> {
+    let config = Config::new(&raw_dsn, params)?;
+    let mut hosts: Vec<&Host> = config.hosts.iter().collect();
+    if config.load_balance_hosts == LoadBalanceHosts::Random {
+        shuffle(&mut hosts);
+    }
+    // Like libpq, standbys are looked for among all the hosts before falling back
+    let all_target_session_attrs = match config.target_session_attrs {
+        TargetSessionAttrs::PreferStandby => {
+            vec![TargetSessionAttrs::Standby, TargetSessionAttrs::Any]
+        }
+        target_session_attrs => vec![target_session_attrs],
+    };
+    let mut errors = vec![];
+    for target_session_attrs in all_target_session_attrs.into_iter() {
+        for host in hosts.iter() {
+            let connection = connect_host(&config, host, target_session_attrs);
+            let connection = match config.connect_timeout {
+                Some(connect_timeout) => time::timeout(connect_timeout, connection)
+                    .await
+                    .unwrap_or(Err(ConnectionError::ConnectTimeoutError(connect_timeout))),
+                None => connection.await,
+            };
+            match connection {
+                Ok(connection) => return Ok(connection),
+                Err(err) => errors.push((format!("{}:{}", host.host, host.port), err)),
+            }
+        }
+    }
+    // There's always at least one host
+    if errors.len() == 1 {
+        return Err(errors.remove(0).1);
+    }
+    Err(ConnectionError::HostsError(errors))
+}
+
+async fn connect_host(
+    config: &Config,
+    host: &Host,
+    target_session_attrs: TargetSessionAttrs,
+) -> Result<Connection, ConnectionError> {
+    let (stream, server_cert) = open_stream(host, &config.tls, &config.keepalives).await?;
     let mut connection = Connection::new(stream);
-    println!("Connected!");
-    let mut params = vec![("user".to_owned(), parsed_dsn.user.to_owned())];
-    if let Some(database) = parsed_dsn.dbname {
-        params.push(("database".to_owned(), database))
+    let mut params = vec![("user".to_owned(), config.user.to_owned())];
+    if let Some(database) = &config.dbname {
+        params.push(("database".to_owned(), database.to_owned()))
+    }
+    if let Some(application_name) = &config.application_name {
+        params.push(("application_name".to_owned(), application_name.to_owned()))
+    }
+    if let Some(options) = &config.options {
+        params.push(("options".to_owned(), options.to_owned()))
     }
     let startup = StartupMessage::new(params);
 
     connection.write_message(startup).await?;
     let message = connection.read_message().await?;
+    if config.tls.channel_binding == ChannelBinding::Require {
+        if let BackendMessage::AuthenticationOk(_)
+        | BackendMessage::AuthenticationCleartextPassword(_)
+        | BackendMessage::AuthenticationMD5Password(_) = message
+        {
+            // Checked before sending the password, which could be intercepted
+            return Err(ConnectionError::ChannelBindingError(
+                "channel binding is required, but the server did not request SASL authentication"
+                    .to_owned(),
+            ));
+        }
+    }
+    let password = || {
+        host.password
+            .to_owned()
+            .ok_or_else(|| match &config.pgpass_warning {
+                Some(warning) => dsn::InvalidDsnError::IgnoredPasswordFile(warning.to_owned()),
+                None => dsn::InvalidDsnError::MissingPassword,
+            })
+    };
     match message {
+        // Trust authentication
+        BackendMessage::AuthenticationOk(_) => {}
+        BackendMessage::AuthenticationCleartextPassword(_) => {
+            password_authenticate(&mut connection, password()?).await?;
+        }
+        BackendMessage::AuthenticationMD5Password(auth_md5) => {
+            let hashed_password = md5_password(&config.user, &password()?, auth_md5.salt);
+            password_authenticate(&mut connection, hashed_password).await?;
+        }
         BackendMessage::AuthenticationSASL(auth_sasl) => {
-            let password = parsed_dsn.password.unwrap();
-            sasl_authenticate(&mut connection, &parsed_dsn.user, &password, auth_sasl).await?;
+            sasl_authenticate(
+                &mut connection,
+                &config.user,
+                &password()?,
+                auth_sasl,
+                config.tls.channel_binding,
+                server_cert.as_deref(),
+            )
+            .await?;
         }
-        _ => todo!("Non-SASL auth"),
+        msg => return Err(unexpected_message_error(msg)),
     }
 
+    let mut server_params = HashMap::new();
     loop {
-        let message = connection.read_message().await?;
-        if let BackendMessage::ReadyForQuery(_) = message {
-            break;
+        match connection.read_message().await? {
+            BackendMessage::ReadyForQuery(_) => break,
+            BackendMessage::ParameterStatus(param) => {
+                server_params.insert(param.name, param.value);
+            }
+            BackendMessage::BackendKeyData(key_data) => {
+                // The password isn't needed to cancel queries
+                let host = Host {
+                    password: None,
+                    ..host.to_owned()
+                };
+                connection.cancel_token = Some(CancelToken::new(
+                    host,
+                    config.tls.to_owned(),
+                    config.keepalives.to_owned(),
+                    config.connect_timeout,
+                    key_data,
+                ));
+            }
+            // E.g. when there are too many connections
+            BackendMessage::ErrorResponse(err) => return Err(ConnectionError::from(err)),
+            _ => {}
         }
     }
+    check_target_session_attrs(&mut connection, &server_params, target_session_attrs).await?;
 
-    println!("Fetching PG types...");
     let query_string = r#"
 SELECT oid, typname, typlen
 FROM pg_type
 WHERE typname IN (
-  'int2', 'int4', 'int8', 'numeric', 'float4', 'float8'
+  'int2', 'int4', 'int8', 'numeric', 'float4', 'float8',
+  'bool', 'text', 'varchar', 'bpchar', 'name', 'bytea', 'date', 'timestamp', 'timestamptz'
 );
 "#
     .to_owned();
-    let (_, data_rows_bytes) = connection.fetch_raw(query_string).await?;
+    let (_, data_rows_bytes) = connection.fetch_raw(query_string, &[]).await?;
     let mut pg_types = HashMap::new();
     for drb in data_rows_bytes.into_iter() {
         let mut dr = DataRow::deserialize_body(drb);
@@ -250,7 +907,6 @@ WHERE typname IN (
         pg_types.insert(oid, PgType::new(oid, name, size));
     }
     connection.pg_types = Some(pg_types);
-    println!("PG types: {:?}", connection.pg_types);
-    println!("PG types fetched!");
+    connection.set_query_timeout(config.query_timeout);
     Ok(connection)
 }
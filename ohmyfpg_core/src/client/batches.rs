@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests;
+
+use crate::client::{Connection, FetchError, FetchResult};
+use crate::messages::query::RowDescription;
+use futures::stream::{self, Stream};
+
+/// State of a query whose result is being fetched in batches.
+#[derive(Debug)]
+pub struct FetchBatches {
+    id: u32,
+    desc: RowDescription,
+    batch_rows: usize,
+    done: bool,
+}
+
+impl FetchBatches {
+    pub fn desc(&self) -> &RowDescription {
+        &self.desc
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+enum BatchesState {
+    Pending(String),
+    Started(FetchBatches),
+}
+
+impl Connection {
+    /// Executes the query so that its result can then be read in batches of `batch_rows`
+    /// rows with `next_batch`.
+    ///
+    /// Running another query on the same connection before all the batches are read
+    /// abandons them: their remaining rows are skipped, and `next_batch` then returns
+    /// an error.
+    pub async fn start_fetch_batches(
+        &mut self,
+        query_string: String,
+        batch_rows: usize,
+    ) -> Result<FetchBatches, FetchError> {
+        let desc = self.execute_prepared_query(query_string, &[]).await?;
+        let id = self.next_batches_id;
+        self.next_batches_id = self.next_batches_id.wrapping_add(1);
+        self.unfinished_batches = Some(id);
+        Ok(FetchBatches {
+            id,
+            desc,
+            batch_rows: batch_rows.max(1),
+            done: false,
+        })
+    }
+
+    /// Reads and decodes the next batch of rows, or returns `None` once all of them
+    /// have been read.
+    pub async fn next_batch(
+        &mut self,
+        batches: &mut FetchBatches,
+    ) -> Result<Option<FetchResult>, FetchError> {
+        if batches.done {
+            return Ok(None);
+        }
+        // Taken while reading, so that the `Sync` sent on `CommandComplete` doesn't
+        // skip the rest of the response
+        if self.unfinished_batches.take() != Some(batches.id) {
+            batches.done = true;
+            return Err(FetchError::InvalidStateError(
+                "the batches were abandoned by running another query".to_owned(),
+            ));
+        }
+
+        let (data_rows_bytes, done) = self.read_data_rows(batches.batch_rows).await?;
+        batches.done = done;
+        if !done {
+            self.unfinished_batches = Some(batches.id);
+        }
+        if data_rows_bytes.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            self.decode_columns(&batches.desc, data_rows_bytes)?
+                .into_iter()
+                .collect(),
+        ))
+    }
+
+    /// Fetches the result of the query as a stream of columnar batches of at most
+    /// `batch_rows` rows each. Every batch is decoded as soon as its rows arrive, so
+    /// that only one batch at a time is kept in memory.
+    ///
+    /// If the stream is dropped before being consumed entirely, the remaining rows are
+    /// skipped when running the next query on the same connection.
+    pub fn fetch_batches(
+        &mut self,
+        query_string: String,
+        batch_rows: usize,
+    ) -> impl Stream<Item = Result<FetchResult, FetchError>> + '_ {
+        stream::try_unfold(
+            (self, BatchesState::Pending(query_string)),
+            move |(conn, state)| async move {
+                let mut batches = match state {
+                    BatchesState::Pending(query_string) => {
+                        conn.start_fetch_batches(query_string, batch_rows).await?
+                    }
+                    BatchesState::Started(batches) => batches,
+                };
+                let batch = conn.next_batch(&mut batches).await?;
+                Ok(batch.map(|batch| (batch, (conn, BatchesState::Started(batches)))))
+            },
+        )
+    }
+}
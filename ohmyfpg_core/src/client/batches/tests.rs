@@ -0,0 +1,111 @@
+use crate::client::mock_server::{connection, query_responses};
+use crate::client::{FetchError, MessageReadError};
+use futures::StreamExt;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+#[tokio::test]
+async fn test_abandoned_batches() {
+    let (mut conn, mut server) = connection();
+    server
+        .write_all(&[query_responses(&[1, 2, 3]), query_responses(&[4])].concat())
+        .await
+        .unwrap();
+
+    let mut batches = conn
+        .start_fetch_batches("SELECT a FROM t".to_owned(), 1)
+        .await
+        .unwrap();
+    let batch = conn.next_batch(&mut batches).await.unwrap().unwrap();
+    assert_eq!(batch["a"].bytes, 1i32.to_be_bytes());
+
+    // The rows left are skipped before reading the response of the next query
+    let res = conn.fetch("SELECT 4".to_owned(), &[]).await.unwrap();
+    assert_eq!(res["a"].bytes, 4i32.to_be_bytes());
+    assert!(matches!(
+        conn.next_batch(&mut batches).await,
+        Err(FetchError::InvalidStateError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_dropped_batches_stream() {
+    let (mut conn, mut server) = connection();
+    server
+        .write_all(&[query_responses(&[1, 2, 3]), query_responses(&[4])].concat())
+        .await
+        .unwrap();
+
+    {
+        let batches = conn.fetch_batches("SELECT a FROM t".to_owned(), 1);
+        futures::pin_mut!(batches);
+        let batch = batches.next().await.unwrap().unwrap();
+        assert_eq!(batch["a"].bytes, 1i32.to_be_bytes());
+    }
+
+    let res = conn.fetch("SELECT 4".to_owned(), &[]).await.unwrap();
+    assert_eq!(res["a"].bytes, 4i32.to_be_bytes());
+}
+
+#[tokio::test]
+async fn test_query_timeout_whole_response() {
+    let (mut conn, mut server) = connection();
+    let responses = query_responses(&(0..20).collect::<Vec<i32>>());
+    conn.set_query_timeout(Some(Duration::from_millis(100)));
+
+    // The server keeps sending rows, but the whole response takes longer than the
+    // timeout
+    let _server = tokio::spawn(async move {
+        for chunk in responses.chunks(8) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            server.write_all(chunk).await.unwrap();
+        }
+        server
+    });
+    let mut batches = conn
+        .start_fetch_batches("SELECT a FROM t".to_owned(), 1)
+        .await
+        .unwrap();
+    let mut rows = 0;
+    let res = loop {
+        match conn.next_batch(&mut batches).await {
+            Ok(Some(_)) => rows += 1,
+            res => break res,
+        }
+    };
+    assert!(matches!(
+        res,
+        Err(FetchError::MessageReadError(
+            MessageReadError::QueryTimeoutError(_)
+        ))
+    ));
+    assert!(rows < 20);
+}
+
+#[tokio::test]
+async fn test_query_timeout_slow_consumer() {
+    let (mut conn, mut server) = connection();
+    server
+        .write_all(&[query_responses(&[1, 2, 3]), query_responses(&[4])].concat())
+        .await
+        .unwrap();
+    conn.set_query_timeout(Some(Duration::from_millis(50)));
+
+    let mut batches = conn
+        .start_fetch_batches("SELECT a FROM t".to_owned(), 1)
+        .await
+        .unwrap();
+    assert!(conn.next_batch(&mut batches).await.unwrap().is_some());
+    // The rest of the response is already received, but past the deadline
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(matches!(
+        conn.next_batch(&mut batches).await,
+        Err(FetchError::MessageReadError(
+            MessageReadError::QueryTimeoutError(_)
+        ))
+    ));
+
+    // The rest of the response is skipped
+    let res = conn.fetch("SELECT 4".to_owned(), &[]).await.unwrap();
+    assert_eq!(res["a"].bytes, 4i32.to_be_bytes());
+}
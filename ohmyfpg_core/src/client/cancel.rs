@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests;
+use crate::client::config::{Host, KeepaliveOptions};
+use crate::client::{open_stream, Connection, ConnectionError, FetchError, TlsOptions};
+use crate::messages::startup::CancelRequest;
+use crate::messages::{BackendKeyData, SerializeMessage};
+use futures::future::try_join_all;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::{self, Duration};
+
+/// Cancels the query being run by a connection. It's obtained with
+/// `Connection::cancel_token`, and can be cloned and used from any task, e.g. while
+/// the connection is busy fetching.
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    host: Host,
+    tls: TlsOptions,
+    keepalives: KeepaliveOptions,
+    connect_timeout: Option<Duration>,
+    key_data: BackendKeyData,
+}
+
+impl CancelToken {
+    pub fn new(
+        host: Host,
+        tls: TlsOptions,
+        keepalives: KeepaliveOptions,
+        connect_timeout: Option<Duration>,
+        key_data: BackendKeyData,
+    ) -> Self {
+        CancelToken {
+            host,
+            tls,
+            keepalives,
+            connect_timeout,
+            key_data,
+        }
+    }
+
+    /// Asks the server to cancel the query being run, over a new connection to the
+    /// same host. The cancelled query fails with an error of code `57014`.
+    ///
+    /// Like with libpq, the request has no effect when it arrives after the query
+    /// completed.
+    pub async fn cancel(&self) -> Result<(), ConnectionError> {
+        match self.connect_timeout {
+            Some(connect_timeout) => time::timeout(connect_timeout, self.send_cancel_request())
+                .await
+                .unwrap_or(Err(ConnectionError::ConnectTimeoutError(connect_timeout))),
+            None => self.send_cancel_request().await,
+        }
+    }
+
+    async fn send_cancel_request(&self) -> Result<(), ConnectionError> {
+        let (mut stream, _) = open_stream(&self.host, &self.tls, &self.keepalives).await?;
+        let request = CancelRequest::new(self.key_data.process_id, self.key_data.secret_key);
+        stream.write_all(&request.serialize()).await?;
+        stream.flush().await?;
+        // The server closes the connection once the request is processed. Errors
+        // are ignored, e.g. when it's closed without a TLS `close_notify`.
+        let mut buf = [0; 1];
+        while let Ok(1..) = stream.read(&mut buf).await {}
+        Ok(())
+    }
+}
+
+/// Cancels the queries of several connections at once, e.g. the partitions of
+/// `fetch_partitioned_with_cancel_tokens`. It can be cloned and used from any task.
+#[derive(Debug, Clone, Default)]
+pub struct CancelTokens(Arc<Mutex<CancelTokensState>>);
+
+#[derive(Debug, Default)]
+struct CancelTokensState {
+    tokens: Vec<CancelToken>,
+    cancelled: bool,
+}
+
+impl CancelTokens {
+    pub fn new() -> Self {
+        CancelTokens::default()
+    }
+
+    /// Adds the token of the connection, whose query is cancelled with the others.
+    ///
+    /// Fails when the queries were cancelled already, so that the connections opened
+    /// meanwhile don't start theirs.
+    pub fn add(&self, connection: &Connection) -> Result<(), ConnectionError> {
+        let mut state = self.0.lock().unwrap();
+        if state.cancelled {
+            return Err(ConnectionError::from(FetchError::InvalidStateError(
+                "the query was cancelled".to_owned(),
+            )));
+        }
+        state.tokens.extend(connection.cancel_token());
+        Ok(())
+    }
+
+    /// Asks the servers to cancel the queries of all the connections added so far.
+    pub async fn cancel(&self) -> Result<(), ConnectionError> {
+        let tokens = {
+            let mut state = self.0.lock().unwrap();
+            state.cancelled = true;
+            state.tokens.clone()
+        };
+        try_join_all(tokens.iter().map(|token| token.cancel())).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use super::*;
+use crate::client::mock_server::connection;
+
+#[tokio::test]
+async fn test_cancel_tokens_add_after_cancel() {
+    let (conn, _server) = connection();
+    let cancel_tokens = CancelTokens::new();
+    cancel_tokens.add(&conn).unwrap();
+    cancel_tokens.cancel().await.unwrap();
+
+    // Connections opened meanwhile must not start their queries
+    let err = cancel_tokens.add(&conn).unwrap_err();
+    assert!(matches!(
+        err,
+        ConnectionError::FetchError(FetchError::InvalidStateError(_))
+    ));
+}
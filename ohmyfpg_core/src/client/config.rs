@@ -0,0 +1,507 @@
+#[cfg(test)]
+mod tests;
+use crate::client::dsn::{parse_dsn, InvalidDsnError};
+use crate::client::tls::TlsOptions;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const DEFAULT_HOST: &str = "localhost";
+pub const DEFAULT_PORT: u16 = 5432;
+
+/// Environment variables used as defaults, like libpq does.
+///
+/// Reference: https://www.postgresql.org/docs/current/libpq-envars.html
+const ENV_VARS: [(&str, &str); 15] = [
+    ("PGHOST", "host"),
+    ("PGPORT", "port"),
+    ("PGUSER", "user"),
+    ("PGPASSWORD", "password"),
+    ("PGDATABASE", "dbname"),
+    ("PGAPPNAME", "application_name"),
+    ("PGOPTIONS", "options"),
+    ("PGSSLMODE", "sslmode"),
+    ("PGSSLROOTCERT", "sslrootcert"),
+    ("PGSSLCERT", "sslcert"),
+    ("PGSSLKEY", "sslkey"),
+    ("PGCHANNELBINDING", "channel_binding"),
+    ("PGTARGETSESSIONATTRS", "target_session_attrs"),
+    ("PGLOADBALANCEHOSTS", "load_balance_hosts"),
+    ("PGCONNECT_TIMEOUT", "connect_timeout"),
+];
+
+/// Same as libpq's `target_session_attrs`, i.e. the kind of server to connect to
+/// among the hosts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetSessionAttrs {
+    #[default]
+    Any,
+    /// A session accepting read-write transactions by default.
+    ReadWrite,
+    /// A session accepting only read-only transactions by default.
+    ReadOnly,
+    /// A server not in hot standby mode.
+    Primary,
+    /// A server in hot standby mode.
+    Standby,
+    /// A standby if any, or else any server.
+    PreferStandby,
+}
+
+impl FromStr for TargetSessionAttrs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any" => Ok(TargetSessionAttrs::Any),
+            "read-write" => Ok(TargetSessionAttrs::ReadWrite),
+            "read-only" => Ok(TargetSessionAttrs::ReadOnly),
+            "primary" => Ok(TargetSessionAttrs::Primary),
+            "standby" => Ok(TargetSessionAttrs::Standby),
+            "prefer-standby" => Ok(TargetSessionAttrs::PreferStandby),
+            _ => Err(format!("Unsupported target_session_attrs: {}", s)),
+        }
+    }
+}
+
+/// Same as libpq's `load_balance_hosts`, i.e. the order in which the hosts are
+/// tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadBalanceHosts {
+    /// In the given order.
+    #[default]
+    Disable,
+    /// In random order.
+    Random,
+}
+
+impl FromStr for LoadBalanceHosts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disable" => Ok(LoadBalanceHosts::Disable),
+            "random" => Ok(LoadBalanceHosts::Random),
+            _ => Err(format!("Unsupported load_balance_hosts: {}", s)),
+        }
+    }
+}
+
+/// Same as libpq's `keepalives`, `keepalives_idle`, `keepalives_interval` and
+/// `keepalives_count`, i.e. the TCP keepalive of the connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeepaliveOptions {
+    pub enabled: bool,
+    /// Idle time before sending a keepalive, the system default if `None`.
+    pub idle: Option<Duration>,
+    /// Time before retransmitting an unacknowledged keepalive, the system default if
+    /// `None`.
+    pub interval: Option<Duration>,
+    /// Unacknowledged keepalives before the connection is considered dead, the system
+    /// default if `None`.
+    pub count: Option<u32>,
+}
+
+impl KeepaliveOptions {
+    pub fn new(
+        enabled: bool,
+        idle: Option<Duration>,
+        interval: Option<Duration>,
+        count: Option<u32>,
+    ) -> Self {
+        KeepaliveOptions {
+            enabled,
+            idle,
+            interval,
+            count,
+        }
+    }
+}
+
+impl Default for KeepaliveOptions {
+    fn default() -> Self {
+        Self::new(true, None, None, None)
+    }
+}
+
+/// One of the hosts to connect to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host {
+    pub host: String,
+    pub port: u16,
+    /// Password of the DSN, or else of the password file for this host.
+    pub password: Option<String>,
+}
+
+impl Host {
+    /// Returns the path of the Unix domain socket when the host is a directory, like
+    /// libpq does.
+    pub fn unix_socket(&self) -> Option<PathBuf> {
+        self.host
+            .starts_with('/')
+            .then(|| Path::new(&self.host).join(format!(".s.PGSQL.{}", self.port)))
+    }
+}
+
+/// Connection options resolved from the DSN, the service file, the environment
+/// variables and the password file, in this order of precedence.
+#[derive(Debug)]
+pub struct Config {
+    pub user: String,
+    /// Tried in order until one matches the `target_session_attrs`.
+    pub hosts: Vec<Host>,
+    pub dbname: Option<String>,
+    pub application_name: Option<String>,
+    /// Command-line options sent to the server at startup, e.g. `-c geqo=off`.
+    pub options: Option<String>,
+    pub tls: TlsOptions,
+    pub target_session_attrs: TargetSessionAttrs,
+    pub load_balance_hosts: LoadBalanceHosts,
+    /// Maximum time to connect to each host.
+    pub connect_timeout: Option<Duration>,
+    /// Maximum time to wait for the whole response of a query, checked by the client,
+    /// which then cancels the query.
+    pub query_timeout: Option<Duration>,
+    pub keepalives: KeepaliveOptions,
+    /// Why the password file was ignored, reported if a password is then missing.
+    pub pgpass_warning: Option<String>,
+}
+
+impl Config {
+    /// Resolves the options of the DSN, overridden by `params`.
+    pub fn new(dsn: &str, params: Vec<(String, String)>) -> Result<Self, InvalidDsnError> {
+        Self::new_with_env(dsn, params, |name| std::env::var(name).ok())
+    }
+
+    /// Same as `new`, but reading the environment variables with `env`.
+    pub fn new_with_env<F>(
+        dsn: &str,
+        params: Vec<(String, String)>,
+        env: F,
+    ) -> Result<Self, InvalidDsnError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let dsn_params = parse_dsn(dsn)?.into_iter().chain(params);
+        let mut service = env("PGSERVICE");
+        let mut explicit_params = vec![];
+        for (key, value) in dsn_params {
+            match key.as_str() {
+                "service" => service = Some(value),
+                _ => explicit_params.push((key, value)),
+            }
+        }
+
+        let mut params: Vec<(String, String)> = ENV_VARS
+            .iter()
+            .filter_map(|(name, key)| env(name).map(|value| (key.to_string(), value)))
+            .collect();
+        if let Some(service) = service.filter(|service| !service.is_empty()) {
+            params.extend(service_params(&service, &env)?);
+        }
+        params.extend(explicit_params);
+
+        let mut config = Self::from_params(params, &env)?;
+        match pgpass_file(&env) {
+            Ok(Some(path)) => {
+                for host in config.hosts.iter_mut() {
+                    if host.password.is_none() {
+                        host.password = pgpass_lookup(&path, host, &config.user, &config.dbname);
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(warning) => config.pgpass_warning = Some(warning),
+        }
+        Ok(config)
+    }
+
+    fn from_params<F>(params: Vec<(String, String)>, env: &F) -> Result<Self, InvalidDsnError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut user = None;
+        let mut password = None;
+        let mut host = None;
+        let mut port = None;
+        let mut dbname = None;
+        let mut application_name = None;
+        let mut options = None;
+        let mut tls = TlsOptions::default();
+        let mut target_session_attrs = TargetSessionAttrs::default();
+        let mut load_balance_hosts = LoadBalanceHosts::default();
+        let mut connect_timeout = None;
+        let mut query_timeout = None;
+        let mut keepalives = KeepaliveOptions::default();
+        for (key, value) in params.into_iter() {
+            let parse_error =
+                |msg: String| InvalidDsnError::ParseError(format!("{}: {}", key, msg));
+            match key.as_str() {
+                "user" => user = Some(value),
+                "password" => password = Some(value),
+                "host" => host = Some(value),
+                "port" => port = Some(value),
+                "dbname" => dbname = Some(value),
+                "application_name" => application_name = Some(value),
+                "options" => options = Some(value),
+                "sslmode" => tls.mode = value.parse().map_err(parse_error)?,
+                "sslrootcert" => tls.root_cert = Some(PathBuf::from(value)),
+                "sslcert" => tls.cert = Some(PathBuf::from(value)),
+                "sslkey" => tls.key = Some(PathBuf::from(value)),
+                "channel_binding" => tls.channel_binding = value.parse().map_err(parse_error)?,
+                "target_session_attrs" => {
+                    target_session_attrs = value.parse().map_err(parse_error)?
+                }
+                "load_balance_hosts" => load_balance_hosts = value.parse().map_err(parse_error)?,
+                "connect_timeout" => {
+                    connect_timeout = parse_timeout(&value).map_err(parse_error)?
+                }
+                "query_timeout" => query_timeout = parse_timeout(&value).map_err(parse_error)?,
+                "keepalives" => {
+                    keepalives.enabled = parse_number::<u32>(&value).map_err(parse_error)? != 0
+                }
+                "keepalives_idle" => {
+                    keepalives.idle = parse_seconds(&value).map_err(parse_error)?
+                }
+                "keepalives_interval" => {
+                    keepalives.interval = parse_seconds(&value).map_err(parse_error)?
+                }
+                "keepalives_count" => {
+                    keepalives.count =
+                        Some(parse_number(&value).map_err(parse_error)?).filter(|count| *count != 0)
+                }
+                _ => return Err(parse_error("Unsupported connection option".to_owned())),
+            }
+        }
+
+        // Same default as libpq, i.e. the operating system user
+        let user = user
+            .filter(|user| !user.is_empty())
+            .or_else(|| env("USER"))
+            .or_else(|| env("USERNAME"))
+            .ok_or(InvalidDsnError::MissingUser)?;
+        let password = password.filter(|password| !password.is_empty());
+        let hosts = parse_hosts(
+            host.as_deref().unwrap_or_default(),
+            port.as_deref().unwrap_or_default(),
+        )?
+        .into_iter()
+        .map(|(host, port)| Host {
+            host,
+            port,
+            password: password.clone(),
+        })
+        .collect();
+        Ok(Config {
+            user,
+            hosts,
+            dbname: dbname.filter(|dbname| !dbname.is_empty()),
+            application_name,
+            options,
+            tls,
+            target_session_attrs,
+            load_balance_hosts,
+            connect_timeout,
+            query_timeout,
+            keepalives,
+            pgpass_warning: None,
+        })
+    }
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid number: {}", value))
+}
+
+/// Parses a timeout in seconds, possibly fractional. Like libpq, zero or less means
+/// no timeout.
+fn parse_timeout(value: &str) -> Result<Option<Duration>, String> {
+    let seconds: f64 = parse_number(value)?;
+    if !seconds.is_finite() {
+        return Err(format!("Invalid number: {}", value));
+    }
+    Ok((seconds > 0.0).then(|| Duration::from_secs_f64(seconds)))
+}
+
+/// Parses whole seconds, in which zero means the system default.
+fn parse_seconds(value: &str) -> Result<Option<Duration>, String> {
+    let seconds: u64 = parse_number(value)?;
+    Ok((seconds != 0).then(|| Duration::from_secs(seconds)))
+}
+
+/// Pairs the comma-separated hosts with the comma-separated ports, of which there
+/// is either one for all the hosts or one per host. Empty values are the defaults.
+fn parse_hosts(hosts: &str, ports: &str) -> Result<Vec<(String, u16)>, InvalidDsnError> {
+    let hosts: Vec<String> = hosts
+        .split(',')
+        .map(|host| match host {
+            "" => DEFAULT_HOST.to_owned(),
+            host => host.to_owned(),
+        })
+        .collect();
+    let ports = ports
+        .split(',')
+        .map(|port| match port {
+            "" => Ok(DEFAULT_PORT),
+            port => port
+                .parse()
+                .map_err(|_| InvalidDsnError::ParseError(format!("port: Invalid port: {}", port))),
+        })
+        .collect::<Result<Vec<u16>, _>>()?;
+    match ports.len() {
+        1 => Ok(hosts.into_iter().map(|host| (host, ports[0])).collect()),
+        n if n == hosts.len() => Ok(hosts.into_iter().zip(ports).collect()),
+        n => Err(InvalidDsnError::ParseError(format!(
+            "port: Could not match {} port numbers to {} hosts",
+            n,
+            hosts.len()
+        ))),
+    }
+}
+
+fn home_file(env: &impl Fn(&str) -> Option<String>, name: &str) -> Option<PathBuf> {
+    env("HOME").map(|home| Path::new(&home).join(name))
+}
+
+/// Returns the password file if it exists, or why it's ignored when it's readable
+/// by others.
+fn pgpass_file(env: &impl Fn(&str) -> Option<String>) -> Result<Option<PathBuf>, String> {
+    let path = match env("PGPASSFILE")
+        .map(PathBuf::from)
+        .or_else(|| home_file(env, ".pgpass"))
+        .filter(|path| path.is_file())
+    {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    if !is_private_file(&path) {
+        return Err(format!(
+            "password file {} has group or world access, permissions should be u=rw (0600) or less",
+            path.display()
+        ));
+    }
+    Ok(Some(path))
+}
+
+/// Returns the password of the first line of the password file matching the host,
+/// formatted as `hostname:port:database:username:password`.
+///
+/// Reference: https://www.postgresql.org/docs/current/libpq-pgpass.html
+fn pgpass_lookup(path: &Path, host: &Host, user: &str, dbname: &Option<String>) -> Option<String> {
+    let port = host.port.to_string();
+    // The server defaults the database to the user
+    let dbname = dbname.as_deref().unwrap_or(user);
+    let matches = |field: &str, value: &str| field == "*" || field == value;
+    // Unix domain sockets are also matched by `localhost`
+    let is_local = host.unix_socket().is_some();
+    fs::read_to_string(path)
+        .ok()?
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(pgpass_fields)
+        .find(|fields| {
+            fields.len() == 5
+                && (matches(&fields[0], &host.host) || (is_local && fields[0] == DEFAULT_HOST))
+                && matches(&fields[1], &port)
+                && matches(&fields[2], dbname)
+                && matches(&fields[3], user)
+        })
+        .and_then(|mut fields| fields.pop())
+}
+
+/// Splits a line of the password file on the unescaped `:`, up to the password,
+/// which is the rest of the line.
+fn pgpass_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => field.push(chars.next().unwrap_or('\\')),
+            ':' if fields.len() < 4 => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(unix)]
+fn is_private_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|metadata| metadata.permissions().mode() & 0o077 == 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_private_file(_path: &Path) -> bool {
+    true
+}
+
+/// Returns the parameters of the service from the user service file, or else from
+/// the system-wide one.
+///
+/// Reference: https://www.postgresql.org/docs/current/libpq-pgservice.html
+fn service_params(
+    service: &str,
+    env: &impl Fn(&str) -> Option<String>,
+) -> Result<Vec<(String, String)>, InvalidDsnError> {
+    let user_file = env("PGSERVICEFILE")
+        .map(PathBuf::from)
+        .or_else(|| home_file(env, ".pg_service.conf"));
+    let system_file = env("PGSYSCONFDIR").map(|dir| Path::new(&dir).join("pg_service.conf"));
+    for path in [user_file, system_file].into_iter().flatten() {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if let Some(params) = parse_service_file(&content, service).map_err(|line| {
+            InvalidDsnError::ParseError(format!(
+                "Syntax error in service file {}, line {}",
+                path.display(),
+                line
+            ))
+        })? {
+            return Ok(params);
+        }
+    }
+    Err(InvalidDsnError::ServiceNotFound(service.to_owned()))
+}
+
+/// Returns the parameters of the `[service]` section of the INI-like service file,
+/// if any, or else the number of the invalid line.
+fn parse_service_file(
+    content: &str,
+    service: &str,
+) -> Result<Option<Vec<(String, String)>>, usize> {
+    let mut params = None;
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[') {
+            let section = section.strip_suffix(']').ok_or(i + 1)?;
+            if params.is_some() {
+                break;
+            }
+            if section == service {
+                params = Some(vec![]);
+            }
+            continue;
+        }
+        if let Some(params) = params.as_mut() {
+            match line.split_once('=') {
+                Some((key, _)) if key.trim() == "service" => return Err(i + 1),
+                Some((key, value)) => params.push((key.trim().to_owned(), value.trim().to_owned())),
+                None => return Err(i + 1),
+            }
+        }
+    }
+    Ok(params)
+}
@@ -0,0 +1,306 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{
+    quote_identifier, ColumnResult, CommandTag, Connection, FetchError, FetchResult,
+};
+use crate::messages::copy::{CopyData, CopyDone};
+use crate::messages::query::{
+    CommandComplete, Describe, Parse, Query, RowDescription, Sync, Target,
+};
+use crate::messages::{BackendMessage, DeserializeMessage, RawTypedBackendMessage};
+use rayon::prelude::*;
+
+// Reference: https://www.postgresql.org/docs/current/sql-copy.html#id-1.9.3.55.9.4
+const COPY_BINARY_SIGNATURE: &[u8; 11] = b"PGCOPY\n\xff\r\n\0";
+/// Signature, flags and header extension length.
+const COPY_BINARY_HEADER_SIZE: usize = 11 + 4 + 4;
+/// Number of rows encoded by each task and sent as a single `CopyData`.
+const COPY_CHUNK_ROWS: usize = 16 * 1024;
+/// Number of chunks encoded in parallel before being sent, which bounds the memory
+/// used by the encoded data.
+const COPY_CHUNKS_PER_WINDOW: usize = 64;
+
+impl Connection {
+    /// Inserts the columns into the table with a binary `COPY FROM STDIN`, and returns
+    /// the number of rows inserted.
+    ///
+    /// Columns are in the same format returned by `fetch`: big-endian values whose
+    /// `dtype` matches the type of the table column, e.g. `>i4` for `int4`. Rows are
+    /// encoded in parallel. `table` is used verbatim, so it may be schema-qualified.
+    pub async fn copy_in_columns(
+        &mut self,
+        table: &str,
+        columns: &[(String, ColumnResult)],
+    ) -> Result<u64, FetchError> {
+        if columns.is_empty() {
+            return Err(FetchError::InvalidParametersError(
+                "no columns to copy".to_owned(),
+            ));
+        }
+        let widths = columns
+            .iter()
+            .map(|(name, col_res)| dtype_width(&col_res.dtype).map(|width| (name, width)))
+            .collect::<Result<Vec<(&String, usize)>, FetchError>>()?;
+        let total_rows = columns[0].1.bytes.len() / widths[0].1;
+        for ((name, col_res), (_, width)) in columns.iter().zip(&widths) {
+            if col_res.bytes.len() != total_rows * width {
+                return Err(FetchError::InvalidParametersError(format!(
+                    "column {} has {} rows, expected {}",
+                    name,
+                    col_res.bytes.len() / width,
+                    total_rows
+                )));
+            }
+        }
+
+        let column_names: Vec<String> = columns
+            .iter()
+            .map(|(name, _)| quote_identifier(name))
+            .collect();
+        let query_string = format!(
+            "COPY {} ({}) FROM STDIN (FORMAT binary)",
+            table,
+            column_names.join(", ")
+        );
+        self.write_message(Query::new(query_string)).await?;
+        match self.read_message().await? {
+            BackendMessage::CopyInResponse(_) => {}
+            msg => {
+                self.read_until_ready().await?;
+                return Err(FetchError::UnexpectedMessageError(msg));
+            }
+        }
+
+        let mut header = COPY_BINARY_SIGNATURE.to_vec();
+        // Flags and header extension length
+        header.extend_from_slice(&0u32.to_be_bytes());
+        header.extend_from_slice(&0u32.to_be_bytes());
+        self.write_message(CopyData::new(header)).await?;
+
+        let total_chunks = total_rows.div_ceil(COPY_CHUNK_ROWS);
+        let mut first_chunk = 0;
+        while first_chunk < total_chunks {
+            let last_chunk = total_chunks.min(first_chunk + COPY_CHUNKS_PER_WINDOW);
+            let chunks: Vec<Vec<u8>> = (first_chunk..last_chunk)
+                .into_par_iter()
+                .map(|chunk| {
+                    let start = chunk * COPY_CHUNK_ROWS;
+                    let end = total_rows.min(start + COPY_CHUNK_ROWS);
+                    encode_rows(columns, &widths, start, end)
+                })
+                .collect();
+            for chunk in chunks.into_iter() {
+                self.write_message(CopyData::new(chunk)).await?;
+            }
+            first_chunk = last_chunk;
+        }
+
+        // File trailer
+        self.write_message(CopyData::new((-1i16).to_be_bytes().to_vec()))
+            .await?;
+        self.write_message(CopyDone::default()).await?;
+
+        let mut rows = None;
+        let mut error = None;
+        loop {
+            match self.read_raw_typed_message().await? {
+                RawTypedBackendMessage::CommandComplete(body) => {
+                    let tag = CommandComplete::deserialize_body(body).tag;
+                    rows = CommandTag::parse(&tag).rows;
+                }
+                RawTypedBackendMessage::ReadyForQuery(_) => break,
+                RawTypedBackendMessage::NoticeResponse(_) => {}
+                msg => {
+                    error.get_or_insert(FetchError::UnexpectedMessageError(msg.parse()));
+                }
+            }
+        }
+
+        error.map_or(Ok(rows.unwrap_or_default()), Err)
+    }
+
+    /// Fetches the result of the query with a binary `COPY TO STDOUT` rather than the
+    /// extended query protocol, which spares the framing of each row in its own
+    /// message. The result is the same as `fetch`, but parameters aren't supported.
+    pub async fn fetch_copy(&mut self, query_string: String) -> Result<FetchResult, FetchError> {
+        let query_string = query_string.trim_end().trim_end_matches(';');
+        let desc = self.describe(query_string.to_owned()).await?;
+        self.write_message(Query::new(format!(
+            "COPY ({}) TO STDOUT (FORMAT binary)",
+            query_string
+        )))
+        .await?;
+        match self.read_message().await? {
+            BackendMessage::CopyOutResponse(_) => {}
+            msg => {
+                self.read_until_ready().await?;
+                return Err(FetchError::UnexpectedMessageError(msg));
+            }
+        }
+
+        let mut decoder = CopyOutDecoder::new();
+        let mut error = None;
+        loop {
+            match self.read_raw_typed_message().await? {
+                RawTypedBackendMessage::CopyData(body) => decoder.push(body),
+                RawTypedBackendMessage::CopyDone(_)
+                | RawTypedBackendMessage::CommandComplete(_)
+                | RawTypedBackendMessage::NoticeResponse(_) => {}
+                RawTypedBackendMessage::ReadyForQuery(_) => break,
+                msg => {
+                    error.get_or_insert(FetchError::UnexpectedMessageError(msg.parse()));
+                }
+            }
+        }
+        if let Some(err) = error {
+            return Err(err);
+        }
+
+        Ok(self
+            .decode_columns(&desc, decoder.rows)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Returns the description of the rows returned by the query without running it.
+    async fn describe(&mut self, query_string: String) -> Result<RowDescription, FetchError> {
+        self.write_message(Parse::new(query_string)).await?;
+        self.write_message(Describe::new(Target::Statement("".to_owned())))
+            .await?;
+        self.write_message(Sync::default()).await?;
+        let mut desc = None;
+        let mut error = None;
+        loop {
+            match self.read_message().await? {
+                BackendMessage::RowDescription(row_desc) => desc = Some(row_desc),
+                BackendMessage::NoData(_) => desc = Some(RowDescription::new(vec![])),
+                BackendMessage::ParseComplete(_)
+                | BackendMessage::ParameterDescription(_)
+                | BackendMessage::NoticeResponse(_) => {}
+                BackendMessage::ReadyForQuery(_) => break,
+                msg => {
+                    error.get_or_insert(FetchError::UnexpectedMessageError(msg));
+                }
+            }
+        }
+
+        error.map_or_else(|| Ok(desc.unwrap()), Err)
+    }
+
+    /// Skips every message until the server is ready for the next query.
+    async fn read_until_ready(&mut self) -> Result<(), FetchError> {
+        loop {
+            if let BackendMessage::ReadyForQuery(_) = self.read_message().await? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Splits the data stream of a binary `COPY TO STDOUT` into rows.
+///
+/// Each tuple has the same layout as the body of a `DataRow`, so that rows can be
+/// decoded into columns the same way.
+struct CopyOutDecoder {
+    header_read: bool,
+    /// Data of an incomplete tuple, split across `CopyData`s.
+    pending: Vec<u8>,
+    rows: Vec<Vec<u8>>,
+}
+
+impl CopyOutDecoder {
+    fn new() -> Self {
+        CopyOutDecoder {
+            header_read: false,
+            pending: vec![],
+            rows: vec![],
+        }
+    }
+
+    fn push(&mut self, data: Vec<u8>) {
+        let data = if self.pending.is_empty() {
+            data
+        } else {
+            let mut pending = std::mem::take(&mut self.pending);
+            pending.extend_from_slice(&data);
+            pending
+        };
+        let mut offset = 0;
+        if !self.header_read {
+            let header_size = data
+                .get(15..COPY_BINARY_HEADER_SIZE)
+                .map(|ext_len| {
+                    COPY_BINARY_HEADER_SIZE
+                        + u32::from_be_bytes(ext_len.try_into().unwrap()) as usize
+                })
+                .filter(|header_size| *header_size <= data.len());
+            match header_size {
+                Some(header_size) => offset = header_size,
+                None => {
+                    self.pending = data;
+                    return;
+                }
+            }
+            self.header_read = true;
+        }
+
+        while let Some(tuple_len) = tuple_len(&data[offset..]) {
+            // Servers send a tuple per `CopyData`, which is kept as is
+            if offset == 0 && tuple_len == data.len() {
+                self.rows.push(data);
+                return;
+            }
+            self.rows.push(data[offset..offset + tuple_len].to_vec());
+            offset += tuple_len;
+        }
+        self.pending = data[offset..].to_vec();
+    }
+}
+
+/// Returns the length of the tuple at the start of `data`, or `None` if it's
+/// incomplete or it's the trailer.
+fn tuple_len(data: &[u8]) -> Option<usize> {
+    let fields_count = i16::from_be_bytes(data.get(0..2)?.try_into().unwrap());
+    if fields_count < 0 {
+        return None;
+    }
+    let mut len = 2;
+    for _ in 0..fields_count {
+        let field_len = i32::from_be_bytes(data.get(len..len + 4)?.try_into().unwrap());
+        len += 4 + field_len.max(0) as usize;
+    }
+    (data.len() >= len).then_some(len)
+}
+
+/// Encodes the rows from `start` to `end` as binary `COPY` tuples.
+fn encode_rows(
+    columns: &[(String, ColumnResult)],
+    widths: &[(&String, usize)],
+    start: usize,
+    end: usize,
+) -> Vec<u8> {
+    let fields_count: i16 = columns.len().try_into().unwrap();
+    let row_size: usize = 2 + widths.iter().map(|(_, width)| 4 + width).sum::<usize>();
+    let mut buf = Vec::with_capacity((end - start) * row_size);
+    for row in start..end {
+        buf.extend_from_slice(&fields_count.to_be_bytes());
+        for ((_, col_res), (_, width)) in columns.iter().zip(widths) {
+            buf.extend_from_slice(&(*width as i32).to_be_bytes());
+            buf.extend_from_slice(&col_res.bytes[row * width..(row + 1) * width]);
+        }
+    }
+    buf
+}
+
+fn dtype_width(dtype: &str) -> Result<usize, FetchError> {
+    match dtype {
+        "|b1" => Ok(1),
+        ">i2" => Ok(2),
+        ">i4" | ">f4" => Ok(4),
+        ">i8" | ">f8" => Ok(8),
+        dtype => Err(FetchError::InvalidParametersError(format!(
+            "unsupported dtype: {}",
+            dtype
+        ))),
+    }
+}
@@ -0,0 +1,40 @@
+use crate::client::copy::{encode_rows, CopyOutDecoder, COPY_BINARY_SIGNATURE};
+use crate::client::ColumnResult;
+
+#[test]
+fn test_encode_rows() {
+    let columns = vec![
+        (
+            "a".to_owned(),
+            ColumnResult::new(vec![0, 1, 0, 2], ">i2".to_owned()),
+        ),
+        (
+            "b".to_owned(),
+            ColumnResult::new(vec![1, 0], "|b1".to_owned()),
+        ),
+    ];
+    let widths = vec![(&columns[0].0, 2), (&columns[1].0, 1)];
+    assert_eq!(
+        encode_rows(&columns, &widths, 1, 2),
+        vec![0, 2, 0, 0, 0, 2, 0, 2, 0, 0, 0, 1, 0]
+    );
+}
+
+#[test]
+fn test_decode_copy_out() {
+    let mut data = COPY_BINARY_SIGNATURE.to_vec();
+    // Flags, and a header extension of 2 bytes
+    data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 2, 9, 9]);
+    let first_row = vec![0, 2, 0, 0, 0, 2, 0, 1, 255, 255, 255, 255];
+    let second_row = vec![0, 1, 0, 0, 0, 1, 7];
+    data.extend_from_slice(&first_row);
+    data.extend_from_slice(&second_row);
+    data.extend_from_slice(&[255, 255]);
+
+    // Messages boundaries don't have to match the ones of the tuples
+    let mut decoder = CopyOutDecoder::new();
+    for chunk in data.chunks(5) {
+        decoder.push(chunk.to_vec());
+    }
+    assert_eq!(decoder.rows, vec![first_row, second_row]);
+}
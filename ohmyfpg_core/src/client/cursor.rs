@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod tests;
+
+use crate::client::{Connection, FetchError, FetchResult};
+use crate::messages::query::{
+    Bind, Close, Describe, Execute, Flush, Format, Parse, RowDescription, Sync, Target,
+};
+use crate::messages::{BackendMessage, RawTypedBackendMessage, TransactionStatus};
+use std::sync::{Arc, Mutex};
+
+/// Server-side cursor backed by a named portal.
+///
+/// A cursor dropped without being closed is forgotten before the next request, and
+/// the transaction started for the cursors is rolled back once none is left.
+#[derive(Debug)]
+pub struct Cursor {
+    portal_name: String,
+    desc: RowDescription,
+    done: bool,
+    // `None` once the cursor has been closed
+    dropped_cursors: Option<Arc<Mutex<Vec<String>>>>,
+}
+
+impl Cursor {
+    pub fn desc(&self) -> &RowDescription {
+        &self.desc
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+impl Drop for Cursor {
+    fn drop(&mut self) {
+        if let Some(dropped_cursors) = self.dropped_cursors.take() {
+            if let Ok(mut dropped_cursors) = dropped_cursors.lock() {
+                dropped_cursors.push(self.portal_name.to_owned());
+            }
+        }
+    }
+}
+
+impl Connection {
+    /// Opens a server-side cursor on the query, whose rows can then be fetched a few
+    /// at a time with `fetch_cursor`.
+    ///
+    /// Cursors live inside a transaction: unless one is already in progress, it's
+    /// started when the first cursor is opened and committed when the last one is
+    /// closed with `close_cursor`, or rolled back if the last one is dropped instead.
+    pub async fn open_cursor(&mut self, query_string: String) -> Result<Cursor, FetchError> {
+        // The status is stale until the pending rollbacks are done
+        self.write_pending_rollbacks().await?;
+        self.skip_unread_responses().await?;
+        if self.open_cursors.is_empty() && self.transaction_status == TransactionStatus::Idle {
+            self.run_command("BEGIN".to_owned()).await?;
+            self.cursors_transaction = true;
+        }
+        self.next_portal_id += 1;
+        let portal_name = format!("ohmyfpg_portal_{}", self.next_portal_id);
+
+        self.write_message(Parse::new(query_string)).await?;
+        self.write_message(Bind::new_with_portal(
+            portal_name.to_owned(),
+            Format::Binary,
+        ))
+        .await?;
+        self.write_message(Describe::new(Target::Portal(portal_name.to_owned())))
+            .await?;
+        self.write_message(Flush::default()).await?;
+
+        let res = async {
+            match self.read_message().await? {
+                BackendMessage::ParseComplete(_) => {}
+                msg => return Err(FetchError::UnexpectedMessageError(msg)),
+            }
+            match self.read_message().await? {
+                BackendMessage::BindComplete(_) => {}
+                msg => return Err(FetchError::UnexpectedMessageError(msg)),
+            }
+            match self.read_message().await? {
+                BackendMessage::RowDescription(row_desc) => Ok(row_desc),
+                msg => Err(FetchError::UnexpectedMessageError(msg)),
+            }
+        }
+        .await;
+
+        match res {
+            Ok(desc) => {
+                self.open_cursors.insert(portal_name.to_owned());
+                Ok(Cursor {
+                    portal_name,
+                    desc,
+                    done: false,
+                    dropped_cursors: Some(Arc::clone(&self.dropped_cursors)),
+                })
+            }
+            Err(err) => {
+                self.abort_cursors().await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Fetches the next `max_rows` rows of the cursor, or `None` once all of them have
+    /// been fetched. A `max_rows` of `0` fetches all the remaining rows.
+    pub async fn fetch_cursor(
+        &mut self,
+        cursor: &mut Cursor,
+        max_rows: u32,
+    ) -> Result<Option<FetchResult>, FetchError> {
+        if cursor.done {
+            return Ok(None);
+        }
+
+        self.write_message(Execute::new(cursor.portal_name.to_owned(), max_rows))
+            .await?;
+        self.write_message(Flush::default()).await?;
+
+        let mut data_rows = vec![];
+        loop {
+            match self.read_raw_typed_message().await? {
+                RawTypedBackendMessage::DataRow(body) => data_rows.push(body),
+                RawTypedBackendMessage::PortalSuspended(_) => break,
+                RawTypedBackendMessage::CommandComplete(_) => {
+                    cursor.done = true;
+                    break;
+                }
+                msg => {
+                    cursor.done = true;
+                    self.abort_cursors().await?;
+                    return Err(FetchError::UnexpectedMessageError(msg.parse()));
+                }
+            }
+        }
+
+        if data_rows.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            self.decode_columns(&cursor.desc, data_rows)?
+                .into_iter()
+                .collect(),
+        ))
+    }
+
+    /// Closes the cursor, committing the transaction if it was the last open one.
+    pub async fn close_cursor(&mut self, mut cursor: Cursor) -> Result<(), FetchError> {
+        cursor.dropped_cursors = None;
+        self.write_message(Close::new(Target::Portal(cursor.portal_name.to_owned())))
+            .await?;
+        self.write_message(Sync::default()).await?;
+        let mut error = None;
+        loop {
+            match self.read_message().await? {
+                BackendMessage::CloseComplete(_) => {}
+                BackendMessage::ReadyForQuery(_) => break,
+                msg => {
+                    error.get_or_insert(FetchError::UnexpectedMessageError(msg));
+                }
+            }
+        }
+
+        self.open_cursors.remove(&cursor.portal_name);
+        if self.open_cursors.is_empty() && self.cursors_transaction {
+            self.cursors_transaction = false;
+            self.run_command("COMMIT".to_owned()).await?;
+        }
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Recovers from an error while using cursors. The error aborts the transaction,
+    /// hence all the open cursors are gone too. A transaction that wasn't started by
+    /// the cursors is left to be rolled back by its owner.
+    async fn abort_cursors(&mut self) -> Result<(), FetchError> {
+        self.sync().await?;
+        self.open_cursors.clear();
+        if !self.cursors_transaction {
+            return Ok(());
+        }
+        self.cursors_transaction = false;
+        self.run_command("ROLLBACK".to_owned()).await
+    }
+}
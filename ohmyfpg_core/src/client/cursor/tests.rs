@@ -0,0 +1,54 @@
+use crate::client::mock_server::backend_message;
+use crate::client::Connection;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Responses to `BEGIN` and to opening a cursor on a query returning no columns.
+fn open_cursor_responses() -> Vec<u8> {
+    [
+        backend_message(b'C', b"BEGIN\0"),
+        backend_message(b'Z', b"T"),
+        backend_message(b'1', &[]),
+        backend_message(b'2', &[]),
+        backend_message(b'T', &0i16.to_be_bytes()),
+    ]
+    .concat()
+}
+
+#[tokio::test]
+async fn test_dropped_cursor() {
+    let (stream, mut server) = tokio::io::duplex(64 * 1024);
+    let rollback_responses = [
+        backend_message(b'C', b"ROLLBACK\0"),
+        backend_message(b'Z', b"I"),
+    ]
+    .concat();
+    server
+        .write_all(
+            &[
+                open_cursor_responses(),
+                rollback_responses,
+                open_cursor_responses(),
+            ]
+            .concat(),
+        )
+        .await
+        .unwrap();
+    let mut conn = Connection::new(Box::new(stream));
+
+    let cursor = conn.open_cursor("SELECT".to_owned()).await.unwrap();
+    assert!(conn.cursors_transaction);
+    drop(cursor);
+
+    // The transaction of the dropped cursor is rolled back and a new one is started
+    let _cursor = conn.open_cursor("SELECT".to_owned()).await.unwrap();
+    assert!(conn.cursors_transaction);
+    assert_eq!(conn.open_cursors.len(), 1);
+
+    let mut buf = vec![0; 64 * 1024];
+    let n = server.read(&mut buf).await.unwrap();
+    let sent = String::from_utf8_lossy(&buf[..n]);
+    assert_eq!(sent.matches("BEGIN").count(), 2);
+    let rollback = sent.find("ROLLBACK").unwrap();
+    assert!(sent.find("BEGIN").unwrap() < rollback);
+    assert!(rollback < sent.rfind("BEGIN").unwrap());
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{ColumnResult, Connection, FetchError, FetchPolarsError};
+use polars::prelude::{Column, DataFrame};
+
+impl Connection {
+    /// Fetches the result of the query as a `polars::DataFrame`.
+    ///
+    /// The columns are built straight from the decoded column buffers, and they
+    /// follow the same order of the fields in the query.
+    pub async fn fetch_polars(
+        &mut self,
+        query_string: String,
+    ) -> Result<DataFrame, FetchPolarsError> {
+        let (desc, data_rows_bytes) = self.fetch_raw(query_string, &[]).await?;
+        let columns = self.decode_columns(&desc, data_rows_bytes)?;
+        to_dataframe(columns)
+    }
+}
+
+/// Converts the decoded columns into a `polars::DataFrame`.
+pub fn to_dataframe(columns: Vec<(String, ColumnResult)>) -> Result<DataFrame, FetchPolarsError> {
+    let columns = columns
+        .into_iter()
+        .map(|(name, col_res)| to_column(name, col_res))
+        .collect::<Result<_, _>>()?;
+    Ok(DataFrame::new(columns)?)
+}
+
+fn to_column(name: String, col_res: ColumnResult) -> Result<Column, FetchPolarsError> {
+    Ok(match col_res.dtype.as_str() {
+        ">i2" => Column::new(name.into(), col_res.to_native_vec(i16::from_be_bytes)),
+        ">i4" => Column::new(name.into(), col_res.to_native_vec(i32::from_be_bytes)),
+        ">i8" => Column::new(name.into(), col_res.to_native_vec(i64::from_be_bytes)),
+        ">f4" => Column::new(name.into(), col_res.to_native_vec(f32::from_be_bytes)),
+        ">f8" => Column::new(name.into(), col_res.to_native_vec(f64::from_be_bytes)),
+        dtype => {
+            return Err(FetchPolarsError::FetchError(
+                FetchError::UnsupportedTypeError(format!(
+                    "column \"{}\" has dtype {}",
+                    name, dtype
+                )),
+            ))
+        }
+    })
+}
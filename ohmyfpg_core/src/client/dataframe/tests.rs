@@ -0,0 +1,30 @@
+use crate::client::dataframe::to_dataframe;
+use crate::client::{ColumnResult, FetchError, FetchPolarsError};
+
+#[test]
+fn test_to_dataframe() {
+    let columns = vec![
+        (
+            "a".to_owned(),
+            ColumnResult::new(vec![0, 1, 0, 2], ">i2".to_owned()),
+        ),
+        (
+            "b".to_owned(),
+            ColumnResult::new(vec![0, 0, 0, 0, 0, 0, 0, 3], ">i4".to_owned()),
+        ),
+    ];
+    let df = to_dataframe(columns).unwrap();
+    assert_eq!(df.shape(), (2, 2));
+    assert_eq!(df.get_column_names(), vec!["a", "b"]);
+
+    let columns = vec![(
+        "c".to_owned(),
+        ColumnResult::new(vec![1, 0], "|b1".to_owned()),
+    )];
+    assert!(matches!(
+        to_dataframe(columns),
+        Err(FetchPolarsError::FetchError(
+            FetchError::UnsupportedTypeError(_)
+        ))
+    ));
+}
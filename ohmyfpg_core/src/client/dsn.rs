@@ -1,42 +1,165 @@
-use regex::Regex;
 pub mod error;
+#[cfg(test)]
+mod tests;
 pub use error::InvalidDsnError;
 
-const PATTERN: &str = r"^(?P<driver>postgres(ql)?)://(((?P<user>[\w\d]+)?(:(?P<password>[^@/:\s]+))?@)?(?P<netloc>[\w\d]+)(:(?P<port>\d+))?/?(?P<dbname>[\w\d]+)?(\?)?(?P<params>.*))?$";
+const URI_SCHEMES: [&str; 2] = ["postgresql://", "postgres://"];
 
-#[derive(Debug)]
-pub struct Dsn {
-    pub user: String,
-    pub address: String,
-    pub password: Option<String>,
-    pub dbname: Option<String>,
-    pub params: Option<String>,
+/// Parses either a URI or a libpq `key=value` connection string into its
+/// parameters, in order. Later parameters override earlier ones.
+///
+/// Reference: https://www.postgresql.org/docs/current/libpq-connect.html#LIBPQ-CONNSTRING
+pub fn parse_dsn(dsn: &str) -> Result<Vec<(String, String)>, InvalidDsnError> {
+    match URI_SCHEMES
+        .iter()
+        .find_map(|scheme| dsn.strip_prefix(scheme))
+    {
+        Some(uri) => parse_uri(uri),
+        None => match dsn.split_once("://") {
+            Some((driver, _)) if !driver.contains('=') => {
+                Err(InvalidDsnError::InvalidDriver(driver.to_owned()))
+            }
+            _ => parse_conninfo(dsn),
+        },
+    }
 }
 
-pub fn parse_dsn(dsn: &str) -> Result<Dsn, InvalidDsnError> {
-    let re = Regex::new(PATTERN).unwrap();
-    let caps = re.captures(dsn).unwrap();
-    match (caps.name("user"), caps.name("netloc")) {
-        (Some(user_match), Some(netloc_match)) => {
-            let user = user_match.as_str().to_owned();
-            let netloc = netloc_match.as_str();
-            let address = caps.name("port").map_or(netloc.to_owned(), |port| {
-                format!("{}:{}", netloc, port.as_str())
-            });
-            let password = caps.name("password").map(|v| v.as_str().to_owned());
-            let dbname = caps.name("dbname").map(|v| v.as_str().to_owned());
-            let params = caps.name("params").map(|v| v.as_str().to_owned());
-            let dsn = Dsn {
-                user,
-                address,
-                password,
-                dbname,
-                params,
+/// Parses the URI without its scheme, i.e.
+/// `[user[:password]@][host][:port][/dbname][?param1=value1&...]`, into parameters.
+fn parse_uri(uri: &str) -> Result<Vec<(String, String)>, InvalidDsnError> {
+    let (uri, query) = uri.split_once('?').unwrap_or((uri, ""));
+    let (authority, path) = uri.split_once('/').unwrap_or((uri, ""));
+    let mut params = vec![];
+
+    let hostspecs = match authority.rsplit_once('@') {
+        Some((userinfo, hostspecs)) => {
+            let (user, password) = match userinfo.split_once(':') {
+                Some((user, password)) => (user, Some(password)),
+                None => (userinfo, None),
             };
-            Ok(dsn)
+            params.push(("user".to_owned(), percent_decode(user)?));
+            if let Some(password) = password {
+                params.push(("password".to_owned(), percent_decode(password)?));
+            }
+            hostspecs
+        }
+        None => authority,
+    };
+    // Like libpq, multiple hosts are comma-separated, and so are their ports
+    let mut hosts = vec![];
+    let mut ports = vec![];
+    for hostspec in hostspecs.split(',') {
+        let (host, port) = parse_hostspec(hostspec)?;
+        hosts.push(percent_decode(host)?);
+        ports.push(percent_decode(port.unwrap_or_default())?);
+    }
+    if hosts.iter().any(|host| !host.is_empty()) {
+        params.push(("host".to_owned(), hosts.join(",")));
+    }
+    if ports.iter().any(|port| !port.is_empty()) {
+        params.push(("port".to_owned(), ports.join(",")));
+    }
+    if !path.is_empty() {
+        params.push(("dbname".to_owned(), percent_decode(path)?));
+    }
+
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        let (key, value) = param.split_once('=').ok_or_else(|| {
+            InvalidDsnError::ParseError(format!("Missing value of parameter: {}", param))
+        })?;
+        params.push((percent_decode(key)?, percent_decode(value)?));
+    }
+    Ok(params)
+}
+
+/// Splits `host[:port]`, in which the host may be an IPv6 literal in brackets.
+fn parse_hostspec(hostspec: &str) -> Result<(&str, Option<&str>), InvalidDsnError> {
+    match hostspec.strip_prefix('[') {
+        Some(hostspec) => {
+            let (host, rest) = hostspec.split_once(']').ok_or_else(|| {
+                InvalidDsnError::ParseError(format!("Unterminated IPv6 address: [{}", hostspec))
+            })?;
+            match rest {
+                "" => Ok((host, None)),
+                rest => match rest.strip_prefix(':') {
+                    Some(port) => Ok((host, Some(port))),
+                    None => Err(InvalidDsnError::ParseError(format!(
+                        "Unexpected characters: {}",
+                        rest
+                    ))),
+                },
+            }
+        }
+        None => Ok(match hostspec.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (hostspec, None),
+        }),
+    }
+}
+
+/// Parses the `key=value` pairs separated by whitespace into parameters. Values
+/// may be single-quoted, and quotes and backslashes are escaped with a backslash.
+fn parse_conninfo(conninfo: &str) -> Result<Vec<(String, String)>, InvalidDsnError> {
+    let mut params = vec![];
+    let mut chars = conninfo.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(params);
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some('=') {
+            return Err(InvalidDsnError::ParseError(format!(
+                "Missing \"=\" after \"{}\"",
+                key
+            )));
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut value = String::new();
+        let quoted = chars.next_if_eq(&'\'').is_some();
+        loop {
+            match chars.next() {
+                Some('\\') => match chars.next() {
+                    Some(c) => value.push(c),
+                    None => value.push('\\'),
+                },
+                Some('\'') if quoted => break,
+                Some(c) if c.is_whitespace() && !quoted => break,
+                Some(c) => value.push(c),
+                None if quoted => {
+                    return Err(InvalidDsnError::ParseError(format!(
+                        "Unterminated quoted value of \"{}\"",
+                        key
+                    )));
+                }
+                None => break,
+            }
+        }
+        params.push((key, value));
+    }
+}
+
+fn percent_decode(s: &str) -> Result<String, InvalidDsnError> {
+    let invalid = || InvalidDsnError::ParseError(format!("Invalid percent-encoding: {}", s));
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [
+                iter.next().ok_or_else(invalid)?,
+                iter.next().ok_or_else(invalid)?,
+            ];
+            let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+        } else {
+            bytes.push(byte);
         }
-        (Some(_), None) => Err(InvalidDsnError::MissingUser),
-        (None, Some(_)) => Err(InvalidDsnError::MissingNetloc),
-        (None, None) => Err(InvalidDsnError::MissingUserAndNetloc),
     }
+    String::from_utf8(bytes).map_err(|_| invalid())
 }
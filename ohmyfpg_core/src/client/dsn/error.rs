@@ -4,8 +4,10 @@ use std::{error, fmt};
 pub enum InvalidDsnError {
     InvalidDriver(String),
     MissingUser,
-    MissingNetloc,
-    MissingUserAndNetloc,
+    MissingPassword,
+    /// The password is missing and the password file was ignored, for the given reason.
+    IgnoredPasswordFile(String),
+    ServiceNotFound(String),
     ParseError(String),
 }
 
@@ -16,8 +18,13 @@ impl fmt::Display for InvalidDsnError {
         match self {
             InvalidDsnError::InvalidDriver(driver) => write!(f, "Invalid driver: {}", driver),
             InvalidDsnError::MissingUser => write!(f, "Missing user in Dsn"),
-            InvalidDsnError::MissingNetloc => write!(f, "Missing netloc in Dsn"),
-            InvalidDsnError::MissingUserAndNetloc => write!(f, "Missing user and netloc in Dsn"),
+            InvalidDsnError::MissingPassword => write!(f, "Missing password in Dsn"),
+            InvalidDsnError::IgnoredPasswordFile(warning) => {
+                write!(f, "Missing password in Dsn, ignored {}", warning)
+            }
+            InvalidDsnError::ServiceNotFound(service) => {
+                write!(f, "Definition of service not found: {}", service)
+            }
             InvalidDsnError::ParseError(dsn) => write!(f, "Parsing error for Dsn: {}", dsn),
         }
     }
@@ -1,5 +1,7 @@
 use crate::client::dsn;
 use crate::messages;
+use std::path::PathBuf;
+use std::time::Duration;
 use std::{error, fmt};
 use tokio::io;
 
@@ -7,6 +9,8 @@ use tokio::io;
 pub enum MessageReadError {
     UnrecognizedMessageError(messages::UnrecognizedMessageError),
     IOError(io::Error),
+    /// The server didn't respond within the query timeout.
+    QueryTimeoutError(Duration),
 }
 
 impl error::Error for MessageReadError {}
@@ -18,6 +22,9 @@ impl fmt::Display for MessageReadError {
                 write!(f, "{}", err)
             }
             MessageReadError::IOError(err) => write!(f, "{}", err),
+            MessageReadError::QueryTimeoutError(timeout) => {
+                write!(f, "Query timed out after {:?}", timeout)
+            }
         }
     }
 }
@@ -38,6 +45,12 @@ impl From<io::Error> for MessageReadError {
 pub enum FetchError {
     MessageReadError(MessageReadError),
     UnexpectedMessageError(messages::BackendMessage),
+    InvalidParametersError(String),
+    InvalidStateError(String),
+    /// The result has a column of a type that can't be decoded.
+    UnsupportedTypeError(String),
+    /// The result has a NULL value, which the columns can't hold.
+    NullValueError(String),
 }
 
 impl error::Error for FetchError {}
@@ -51,6 +64,18 @@ impl fmt::Display for FetchError {
             FetchError::UnexpectedMessageError(msg) => {
                 write!(f, "unexpected message error: {:?}", msg)
             }
+            FetchError::InvalidParametersError(msg) => {
+                write!(f, "invalid parameters error: {}", msg)
+            }
+            FetchError::InvalidStateError(msg) => {
+                write!(f, "invalid state error: {}", msg)
+            }
+            FetchError::UnsupportedTypeError(msg) => {
+                write!(f, "unsupported type error: {}", msg)
+            }
+            FetchError::NullValueError(msg) => {
+                write!(f, "null value error: {}", msg)
+            }
         }
     }
 }
@@ -67,6 +92,87 @@ impl From<io::Error> for FetchError {
     }
 }
 
+#[cfg(feature = "polars")]
+#[derive(Debug)]
+pub enum FetchPolarsError {
+    FetchError(FetchError),
+    PolarsError(polars::error::PolarsError),
+}
+
+#[cfg(feature = "polars")]
+impl error::Error for FetchPolarsError {}
+
+#[cfg(feature = "polars")]
+impl fmt::Display for FetchPolarsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchPolarsError::FetchError(err) => write!(f, "{}", err),
+            FetchPolarsError::PolarsError(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(feature = "polars")]
+impl From<FetchError> for FetchPolarsError {
+    fn from(err: FetchError) -> Self {
+        FetchPolarsError::FetchError(err)
+    }
+}
+
+#[cfg(feature = "polars")]
+impl From<polars::error::PolarsError> for FetchPolarsError {
+    fn from(err: polars::error::PolarsError) -> Self {
+        FetchPolarsError::PolarsError(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    FetchError(FetchError),
+    IOError(io::Error),
+    #[cfg(feature = "parquet")]
+    ParquetError(parquet::errors::ParquetError),
+}
+
+impl error::Error for ExportError {}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::FetchError(err) => write!(f, "{}", err),
+            ExportError::IOError(err) => write!(f, "{}", err),
+            #[cfg(feature = "parquet")]
+            ExportError::ParquetError(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<FetchError> for ExportError {
+    fn from(err: FetchError) -> Self {
+        ExportError::FetchError(err)
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        ExportError::IOError(err)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for ExportError {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        ExportError::ParquetError(err)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<arrow_schema::ArrowError> for ExportError {
+    fn from(err: arrow_schema::ArrowError) -> Self {
+        ExportError::ParquetError(parquet::errors::ParquetError::from(err))
+    }
+}
+
 #[derive(Debug)]
 pub struct ServerError {
     pub severity: String,
@@ -96,11 +202,85 @@ impl From<messages::ErrorResponse> for ServerError {
     }
 }
 
+#[derive(Debug)]
+pub enum TlsError {
+    /// The server doesn't accept TLS connections.
+    NotSupported,
+    InvalidFile(PathBuf, String),
+    InvalidServerName(String),
+    ConfigError(String),
+    HandshakeError(io::Error),
+}
+
+impl error::Error for TlsError {}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::NotSupported => write!(f, "server does not support TLS"),
+            TlsError::InvalidFile(path, msg) => {
+                write!(f, "invalid TLS file {}: {}", path.display(), msg)
+            }
+            TlsError::InvalidServerName(host) => {
+                write!(f, "invalid TLS server name: {}", host)
+            }
+            TlsError::ConfigError(msg) => write!(f, "TLS configuration error: {}", msg),
+            TlsError::HandshakeError(err) => write!(f, "TLS handshake error: {}", err),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SASLAuthError {
+    /// None of the mechanisms offered by the server is supported.
+    UnsupportedMechanisms(Vec<String>),
+    InvalidServerFirst(String),
+    InvalidServerFinal(String),
+    /// The server doesn't know the password, or it's being impersonated.
+    ServerSignatureMismatch,
+    /// The server rejected the authentication exchange with the given reason.
+    Rejected(String),
+}
+
+impl error::Error for SASLAuthError {}
+
+impl fmt::Display for SASLAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SASLAuthError::UnsupportedMechanisms(mechanisms) => {
+                write!(f, "unsupported SASL mechanisms: {}", mechanisms.join(", "))
+            }
+            SASLAuthError::InvalidServerFirst(msg) => {
+                write!(f, "invalid SASL server-first message: {}", msg)
+            }
+            SASLAuthError::InvalidServerFinal(msg) => {
+                write!(f, "invalid SASL server-final message: {}", msg)
+            }
+            SASLAuthError::ServerSignatureMismatch => {
+                write!(f, "SASL server signature mismatch")
+            }
+            SASLAuthError::Rejected(reason) => {
+                write!(f, "SASL authentication rejected: {}", reason)
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ConnectionError {
     InvalidDsnError(dsn::InvalidDsnError),
     FetchError(FetchError),
     ServerError(ServerError),
+    TlsError(TlsError),
+    ChannelBindingError(String),
+    SASLAuthError(SASLAuthError),
+    /// The server doesn't match the `target_session_attrs`.
+    TargetSessionAttrsError(String),
+    /// A host didn't accept the connection within the connect timeout.
+    ConnectTimeoutError(Duration),
+    /// None of the hosts accepted the connection, with the error of each one in the
+    /// order they were tried.
+    HostsError(Vec<(String, ConnectionError)>),
 }
 
 impl error::Error for ConnectionError {}
@@ -111,6 +291,24 @@ impl fmt::Display for ConnectionError {
             ConnectionError::InvalidDsnError(err) => write!(f, "{}", err),
             ConnectionError::FetchError(err) => write!(f, "{}", err),
             ConnectionError::ServerError(err) => write!(f, "{}", err),
+            ConnectionError::TlsError(err) => write!(f, "{}", err),
+            ConnectionError::ChannelBindingError(msg) => {
+                write!(f, "channel binding error: {}", msg)
+            }
+            ConnectionError::SASLAuthError(err) => write!(f, "{}", err),
+            ConnectionError::TargetSessionAttrsError(msg) => {
+                write!(f, "target_session_attrs not matched: {}", msg)
+            }
+            ConnectionError::ConnectTimeoutError(timeout) => {
+                write!(f, "Connection timed out after {:?}", timeout)
+            }
+            ConnectionError::HostsError(errors) => {
+                write!(f, "could not connect to any host")?;
+                for (host, err) in errors.iter() {
+                    write!(f, "; {}: {}", host, err)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -138,3 +336,21 @@ impl From<dsn::InvalidDsnError> for ConnectionError {
         ConnectionError::InvalidDsnError(err)
     }
 }
+
+impl From<TlsError> for ConnectionError {
+    fn from(err: TlsError) -> Self {
+        ConnectionError::TlsError(err)
+    }
+}
+
+impl From<SASLAuthError> for ConnectionError {
+    fn from(err: SASLAuthError) -> Self {
+        ConnectionError::SASLAuthError(err)
+    }
+}
+
+impl From<messages::ErrorResponse> for ConnectionError {
+    fn from(err_resp: messages::ErrorResponse) -> Self {
+        ConnectionError::ServerError(ServerError::from(err_resp))
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{Connection, FetchError};
+use crate::messages::query::{CommandComplete, Sync};
+use crate::messages::{DeserializeMessage, RawTypedBackendMessage};
+use crate::types::ToSql;
+
+/// Outcome of a statement as reported by the server, e.g. `INSERT 0 3` is the
+/// `INSERT` command with 3 rows affected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandTag {
+    pub command: String,
+    /// Number of rows affected or returned, `None` for commands that don't report it
+    /// such as `CREATE TABLE`.
+    pub rows: Option<u64>,
+}
+
+impl CommandTag {
+    pub fn new(command: String, rows: Option<u64>) -> Self {
+        CommandTag { command, rows }
+    }
+
+    /// Parses the tag of a `CommandComplete`.
+    /// Reference: https://www.postgresql.org/docs/current/protocol-message-formats.html
+    pub fn parse(tag: &str) -> Self {
+        let mut words: Vec<&str> = tag.split(' ').collect();
+        let rows = match words.last().map(|w| w.parse::<u64>()) {
+            Some(Ok(rows)) if words.len() > 1 => {
+                words.pop();
+                Some(rows)
+            }
+            _ => None,
+        };
+        // `INSERT` is followed by the OID of the inserted row, which is always 0
+        if rows.is_some() && words[0] == "INSERT" {
+            words.truncate(1);
+        }
+        CommandTag::new(words.join(" "), rows)
+    }
+}
+
+impl Connection {
+    /// Runs a statement, such as an `INSERT`, an `UPDATE` or a DDL, and returns its
+    /// command tag. The rows returned by the statement, if any, are discarded.
+    ///
+    /// An empty query returns an empty command.
+    pub async fn execute(
+        &mut self,
+        query_string: String,
+        params: &[&dyn ToSql],
+    ) -> Result<CommandTag, FetchError> {
+        self.execute_prepared_query(query_string, params).await?;
+        let mut tag = None;
+        loop {
+            match self.read_raw_typed_message().await? {
+                RawTypedBackendMessage::DataRow(_)
+                | RawTypedBackendMessage::NoticeResponse(_)
+                | RawTypedBackendMessage::ParameterStatus(_) => {}
+                RawTypedBackendMessage::CommandComplete(body) => {
+                    tag = Some(CommandTag::parse(
+                        &CommandComplete::deserialize_body(body).tag,
+                    ));
+                    self.write_message(Sync::default()).await?;
+                }
+                RawTypedBackendMessage::EmptyQueryResponse(_) => {
+                    tag = Some(CommandTag::new("".to_owned(), None));
+                    self.write_message(Sync::default()).await?;
+                }
+                RawTypedBackendMessage::ReadyForQuery(_) => break,
+                msg => {
+                    // The server skips every message until the next `Sync` after an error
+                    self.sync().await?;
+                    return Err(FetchError::UnexpectedMessageError(msg.parse()));
+                }
+            }
+        }
+
+        tag.ok_or_else(|| {
+            FetchError::InvalidStateError("the server didn't complete the command".to_owned())
+        })
+    }
+}
@@ -0,0 +1,110 @@
+use crate::client::mock_server::{
+    backend_message, command_complete, connection, data_row, error_response, ready_for_query,
+    row_description,
+};
+use crate::client::{CommandTag, FetchError};
+use crate::messages::BackendMessage;
+use tokio::io::AsyncWriteExt;
+
+#[test]
+fn test_parse_command_tag() {
+    assert_eq!(
+        CommandTag::parse("INSERT 0 3"),
+        CommandTag::new("INSERT".to_owned(), Some(3))
+    );
+    assert_eq!(
+        CommandTag::parse("UPDATE 10"),
+        CommandTag::new("UPDATE".to_owned(), Some(10))
+    );
+    assert_eq!(
+        CommandTag::parse("CREATE TABLE"),
+        CommandTag::new("CREATE TABLE".to_owned(), None)
+    );
+    assert_eq!(
+        CommandTag::parse("BEGIN"),
+        CommandTag::new("BEGIN".to_owned(), None)
+    );
+}
+
+/// Responses to preparing and binding a statement that doesn't return rows.
+fn prepare_responses() -> Vec<u8> {
+    [
+        backend_message(b'1', &[]),
+        backend_message(b't', &0i16.to_be_bytes()),
+        backend_message(b'n', &[]),
+        backend_message(b'2', &[]),
+    ]
+    .concat()
+}
+
+#[tokio::test]
+async fn test_execute() {
+    let (mut conn, mut server) = connection();
+    let select_responses = [
+        backend_message(b'1', &[]),
+        backend_message(b't', &0i16.to_be_bytes()),
+        row_description(&["a"]),
+        backend_message(b'2', &[]),
+        data_row(&[Some(1)]),
+        data_row(&[Some(2)]),
+        command_complete("SELECT 2"),
+        ready_for_query(b'I'),
+    ]
+    .concat();
+    let responses = [
+        prepare_responses(),
+        command_complete("INSERT 0 3"),
+        ready_for_query(b'I'),
+        // The rows are discarded
+        select_responses,
+        prepare_responses(),
+        backend_message(b'I', &[]),
+        ready_for_query(b'I'),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    let tag = conn
+        .execute("INSERT INTO t VALUES (1), (2), (3)".to_owned(), &[])
+        .await
+        .unwrap();
+    assert_eq!(tag, CommandTag::new("INSERT".to_owned(), Some(3)));
+    let tag = conn
+        .execute("SELECT a FROM t".to_owned(), &[])
+        .await
+        .unwrap();
+    assert_eq!(tag, CommandTag::new("SELECT".to_owned(), Some(2)));
+    let tag = conn.execute("".to_owned(), &[]).await.unwrap();
+    assert_eq!(tag, CommandTag::new("".to_owned(), None));
+}
+
+#[tokio::test]
+async fn test_execute_error() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        prepare_responses(),
+        error_response("23505", "duplicate key value"),
+        ready_for_query(b'I'),
+        // Without `CommandComplete`
+        prepare_responses(),
+        ready_for_query(b'I'),
+        prepare_responses(),
+        command_complete("DELETE 1"),
+        ready_for_query(b'I'),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    assert!(matches!(
+        conn.execute("INSERT INTO t VALUES (1)".to_owned(), &[])
+            .await,
+        Err(FetchError::UnexpectedMessageError(
+            BackendMessage::ErrorResponse(_)
+        ))
+    ));
+    assert!(matches!(
+        conn.execute("UPDATE t SET a = 2".to_owned(), &[]).await,
+        Err(FetchError::InvalidStateError(_))
+    ));
+    // The connection is ready for the next statement
+    let tag = conn.execute("DELETE FROM t".to_owned(), &[]).await.unwrap();
+    assert_eq!(tag, CommandTag::new("DELETE".to_owned(), Some(1)));
+}
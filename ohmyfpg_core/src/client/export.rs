@@ -0,0 +1,270 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{Connection, ExportError, FetchError};
+use crate::messages::query::DataRow;
+use crate::messages::DeserializeMessage;
+use crate::server::PgType;
+use crate::types::Value;
+use std::str::FromStr;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+const EXPORT_BATCH_ROWS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+pub enum CsvQuoteStyle {
+    Always,
+    Necessary,
+    NonNumeric,
+    Never,
+}
+
+impl FromStr for CsvQuoteStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(CsvQuoteStyle::Always),
+            "necessary" => Ok(CsvQuoteStyle::Necessary),
+            "non_numeric" => Ok(CsvQuoteStyle::NonNumeric),
+            "never" => Ok(CsvQuoteStyle::Never),
+            _ => Err(format!("Unsupported csv quote style: {}", s)),
+        }
+    }
+}
+
+impl From<CsvQuoteStyle> for csv::QuoteStyle {
+    fn from(quote_style: CsvQuoteStyle) -> Self {
+        match quote_style {
+            CsvQuoteStyle::Always => csv::QuoteStyle::Always,
+            CsvQuoteStyle::Necessary => csv::QuoteStyle::Necessary,
+            CsvQuoteStyle::NonNumeric => csv::QuoteStyle::NonNumeric,
+            CsvQuoteStyle::Never => csv::QuoteStyle::Never,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub quote_style: CsvQuoteStyle,
+    pub header: bool,
+    /// Text written in place of `NULL`s.
+    pub null: String,
+}
+
+impl CsvOptions {
+    pub fn new(delimiter: u8, quote_style: CsvQuoteStyle, header: bool, null: String) -> Self {
+        CsvOptions {
+            delimiter,
+            quote_style,
+            header,
+            null,
+        }
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self::new(b',', CsvQuoteStyle::Necessary, true, "".to_owned())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ExportFormat {
+    Csv(CsvOptions),
+    /// Newline-delimited JSON: one object per row, with the fields in the query order.
+    Ndjson,
+}
+
+impl Connection {
+    /// Writes the result of the query to `writer` in the given format and returns the
+    /// number of rows written.
+    ///
+    /// Rows are encoded and written in batches as they're read, so the whole result is
+    /// never kept in memory.
+    pub async fn export<W>(
+        &mut self,
+        query_string: String,
+        writer: &mut W,
+        format: ExportFormat,
+    ) -> Result<u64, ExportError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let desc = self.execute_prepared_query(query_string, &[]).await?;
+        let names: Vec<String> = desc.fields.iter().map(|f| f.name.to_owned()).collect();
+        let pg_types = desc
+            .fields
+            .iter()
+            .map(|f| match self.pg_type(f.data_type_oid) {
+                Some(pg_type) if Value::can_decode(pg_type) => Ok(pg_type.clone()),
+                Some(pg_type) => Err(FetchError::UnsupportedTypeError(format!(
+                    "column \"{}\" has type {}",
+                    f.name, pg_type.name
+                ))),
+                None => Err(FetchError::UnsupportedTypeError(format!(
+                    "column \"{}\" has type OID {}",
+                    f.name, f.data_type_oid
+                ))),
+            })
+            .collect::<Result<Vec<PgType>, FetchError>>();
+        let pg_types = match pg_types {
+            Ok(pg_types) => pg_types,
+            Err(err) => {
+                self.skip_data_rows().await?;
+                return Err(ExportError::from(err));
+            }
+        };
+
+        let mut encoder = match format {
+            ExportFormat::Csv(options) => RowEncoder::new_csv(options),
+            ExportFormat::Ndjson => RowEncoder::new_ndjson(&names),
+        };
+        writer.write_all(&encoder.header(&names)?).await?;
+
+        let mut total_rows = 0;
+        loop {
+            let (data_rows_bytes, done) = self.read_data_rows(EXPORT_BATCH_ROWS).await?;
+            total_rows += data_rows_bytes.len() as u64;
+            for drb in data_rows_bytes.into_iter() {
+                let dr = DataRow::deserialize_body(drb);
+                let values = dr
+                    .columns
+                    .iter()
+                    .zip(&pg_types)
+                    .map(|(c, pg_type)| Value::decode(pg_type, c.as_deref()))
+                    .collect::<Result<Vec<Value>, FetchError>>();
+                match values {
+                    Ok(values) => encoder.encode(&values)?,
+                    Err(err) => {
+                        if !done {
+                            self.skip_data_rows().await?;
+                        }
+                        return Err(ExportError::from(err));
+                    }
+                }
+            }
+            writer.write_all(&encoder.take()?).await?;
+
+            if done {
+                break;
+            }
+        }
+
+        writer.flush().await?;
+        Ok(total_rows)
+    }
+
+    /// Reads and discards the remaining rows of the query being executed.
+    async fn skip_data_rows(&mut self) -> Result<(), FetchError> {
+        while !self.read_data_rows(EXPORT_BATCH_ROWS).await?.1 {}
+        Ok(())
+    }
+}
+
+enum RowEncoder {
+    Csv {
+        builder: Box<csv::WriterBuilder>,
+        writer: Box<csv::Writer<Vec<u8>>>,
+        options: CsvOptions,
+    },
+    Ndjson {
+        keys: Vec<String>,
+        buf: Vec<u8>,
+    },
+}
+
+impl RowEncoder {
+    fn new_csv(options: CsvOptions) -> Self {
+        let mut builder = csv::WriterBuilder::new();
+        builder
+            .delimiter(options.delimiter)
+            .quote_style(options.quote_style.into());
+        let writer = Box::new(builder.from_writer(vec![]));
+        RowEncoder::Csv {
+            builder: Box::new(builder),
+            writer,
+            options,
+        }
+    }
+
+    fn new_ndjson(names: &[String]) -> Self {
+        // Keys are escaped only once rather than for each row
+        let keys = names
+            .iter()
+            .map(|name| serde_json::to_string(name).unwrap())
+            .collect();
+        RowEncoder::Ndjson { keys, buf: vec![] }
+    }
+
+    fn header(&mut self, names: &[String]) -> Result<Vec<u8>, ExportError> {
+        if let RowEncoder::Csv {
+            writer, options, ..
+        } = self
+        {
+            if options.header {
+                writer.write_record(names).map_err(csv_error)?;
+            }
+        }
+        self.take()
+    }
+
+    fn encode(&mut self, values: &[Value]) -> Result<(), ExportError> {
+        match self {
+            RowEncoder::Csv {
+                writer, options, ..
+            } => {
+                let record = values.iter().map(|v| match v {
+                    Value::Null => options.null.to_owned(),
+                    v => v.to_string(),
+                });
+                writer.write_record(record).map_err(csv_error)
+            }
+            RowEncoder::Ndjson { keys, buf } => {
+                buf.push(b'{');
+                for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+                    if i > 0 {
+                        buf.push(b',');
+                    }
+                    buf.extend_from_slice(key.as_bytes());
+                    buf.push(b':');
+                    buf.extend_from_slice(to_json(value).as_bytes());
+                }
+                buf.extend_from_slice(b"}\n");
+                Ok(())
+            }
+        }
+    }
+
+    /// Takes the bytes encoded so far.
+    fn take(&mut self) -> Result<Vec<u8>, ExportError> {
+        match self {
+            RowEncoder::Csv {
+                builder, writer, ..
+            } => {
+                let writer = std::mem::replace(writer, Box::new(builder.from_writer(vec![])));
+                writer
+                    .into_inner()
+                    .map_err(|err| ExportError::IOError(err.into_error()))
+            }
+            RowEncoder::Ndjson { buf, .. } => Ok(std::mem::take(buf)),
+        }
+    }
+}
+
+fn to_json(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_owned(),
+        Value::Bool(_) | Value::Int2(_) | Value::Int4(_) | Value::Int8(_) => value.to_string(),
+        // JSON has no representation for `NaN`s and infinities
+        Value::Float4(v) if !v.is_finite() => "null".to_owned(),
+        Value::Float8(v) if !v.is_finite() => "null".to_owned(),
+        Value::Numeric(v) if !v.is_finite() => "null".to_owned(),
+        Value::Float4(_) | Value::Float8(_) | Value::Numeric(_) => value.to_string(),
+        _ => serde_json::to_string(&value.to_string()).unwrap(),
+    }
+}
+
+fn csv_error(err: csv::Error) -> ExportError {
+    ExportError::IOError(err.into())
+}
@@ -0,0 +1,40 @@
+use crate::client::export::{CsvOptions, CsvQuoteStyle, RowEncoder};
+use crate::types::{Numeric, Value};
+
+fn rows() -> Vec<Vec<Value>> {
+    // 12345.6789, then numeric `NaN`
+    let numeric = Numeric::decode(&[0, 3, 0, 1, 0, 0, 0, 4, 0, 1, 9, 41, 26, 133]).unwrap();
+    let nan = Numeric::decode(&[0, 0, 0, 0, 192, 0, 0, 0]).unwrap();
+    vec![
+        vec![Value::Int4(1), Value::Numeric(numeric), Value::Float8(0.5)],
+        vec![Value::Null, Value::Numeric(nan), Value::Float8(f64::NAN)],
+    ]
+}
+
+fn encode(mut encoder: RowEncoder, names: &[String]) -> String {
+    let mut out = encoder.header(names).unwrap();
+    for values in rows() {
+        encoder.encode(&values).unwrap();
+    }
+    out.extend(encoder.take().unwrap());
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_encode_csv() {
+    let names = vec!["a".to_owned(), "n".to_owned(), "f".to_owned()];
+    let options = CsvOptions::new(b',', CsvQuoteStyle::Necessary, true, "NULL".to_owned());
+    assert_eq!(
+        encode(RowEncoder::new_csv(options), &names),
+        "a,n,f\n1,12345.6789,0.5\nNULL,NaN,NaN\n"
+    );
+}
+
+#[test]
+fn test_encode_ndjson() {
+    let names = vec!["a".to_owned(), "n".to_owned(), "f".to_owned()];
+    assert_eq!(
+        encode(RowEncoder::new_ndjson(&names), &names),
+        "{\"a\":1,\"n\":12345.6789,\"f\":0.5}\n{\"a\":null,\"n\":null,\"f\":null}\n"
+    );
+}
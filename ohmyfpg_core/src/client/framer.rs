@@ -1,8 +1,10 @@
 use crate::messages::{RawBackendMessage, SerializeMessage};
+#[cfg(test)]
+mod tests;
 use std::fmt;
-use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
-use tokio::net::TcpStream;
+use tokio::io::{
+    self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter, ReadHalf, WriteHalf,
+};
 use tokio::sync::mpsc;
 use tokio::task;
 
@@ -10,14 +12,20 @@ const IO_READ_BUFFER_SIZE: usize = 8 * 1024;
 const FRAMER_BUFFER_SIZE: usize = 100;
 const FRAME_HEADER_SIZE: usize = 5;
 
+/// Stream the connection talks to the server through, e.g. a plain TCP stream or a
+/// TLS one.
+pub trait Stream: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Stream for T {}
+
 pub struct Framer {
     reader: ReadFramer,
     writer: WriteFramer,
 }
 
 impl Framer {
-    pub fn new(stream: TcpStream) -> Self {
-        let (read_half, write_half) = stream.into_split();
+    pub fn new(stream: Box<dyn Stream>) -> Self {
+        let (read_half, write_half) = io::split(stream);
 
         Framer {
             reader: ReadFramer::new(read_half),
@@ -32,26 +40,49 @@ impl Framer {
         self.writer.write_frame(msg.serialize()).await
     }
 
-    pub async fn read_frame(&mut self) -> Option<RawBackendMessage> {
-        self.reader
+    /// Writes the already serialized messages at once.
+    pub async fn write_raw_frames(&mut self, frames: Vec<u8>) -> io::Result<()> {
+        self.writer.write_frame(frames).await
+    }
+
+    /// Sends the buffered messages.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush().await
+    }
+
+    /// Reads the next message, once the buffered messages are sent, as the server
+    /// might wait for them to respond.
+    pub async fn read_frame(&mut self) -> io::Result<Option<RawBackendMessage>> {
+        self.flush().await?;
+        Ok(self
+            .reader
             .read_frame()
             .await
-            .map(|raw_frame| RawBackendMessage::new(raw_frame.0, raw_frame.1))
+            .map(|raw_frame| RawBackendMessage::new(raw_frame.0, raw_frame.1)))
     }
 }
 
+/// Buffers the messages until flushed, so that the ones of a request are sent at
+/// once rather than one write per message.
 struct WriteFramer {
-    write_half_stream: OwnedWriteHalf,
+    write_half_stream: BufWriter<WriteHalf<Box<dyn Stream>>>,
 }
 
 impl WriteFramer {
-    pub fn new(write_half_stream: OwnedWriteHalf) -> Self {
-        WriteFramer { write_half_stream }
+    pub fn new(write_half_stream: WriteHalf<Box<dyn Stream>>) -> Self {
+        WriteFramer {
+            write_half_stream: BufWriter::new(write_half_stream),
+        }
     }
 
     pub async fn write_frame(&mut self, frame: Vec<u8>) -> io::Result<()> {
         self.write_half_stream.write_all(&frame).await
     }
+
+    pub async fn flush(&mut self) -> io::Result<()> {
+        // TLS streams also buffer the encrypted records until flushed
+        self.write_half_stream.flush().await
+    }
 }
 
 type RawReadFrame = ([u8; 1], Vec<u8>);
@@ -64,7 +95,7 @@ struct ReadFramer {
 }
 
 impl ReadFramer {
-    pub fn new(mut read_half_stream: OwnedReadHalf) -> Self {
+    pub fn new(mut read_half_stream: ReadHalf<Box<dyn Stream>>) -> Self {
         let (io_tx, mut io_rx) = mpsc::channel(IO_READ_BUFFER_SIZE);
         let (framer_tx, framer_rx) = mpsc::channel(FRAMER_BUFFER_SIZE);
 
@@ -73,8 +104,12 @@ impl ReadFramer {
                 loop {
                     let mut buffer = [0; IO_READ_BUFFER_SIZE];
                     match read_half_stream.read(&mut buffer[..]).await {
-                        Ok(n_bytes_read) => {
-                            io_tx.send(buffer[0..n_bytes_read].to_vec()).await.unwrap()
+                        // Stops once the server closes the connection, or once the
+                        // connection is dropped
+                        Ok(n_bytes_read) if n_bytes_read > 0 => {
+                            if io_tx.send(buffer[0..n_bytes_read].to_vec()).await.is_err() {
+                                break;
+                            }
                         }
                         _ => {
                             break;
@@ -94,15 +129,18 @@ impl ReadFramer {
                         let body_size: usize = (u32::from_be_bytes(count) - 4).try_into().unwrap();
                         let required_size = FRAME_HEADER_SIZE + body_size;
                         if buf.len() >= required_size {
-                            let body: Vec<u8> = buf[5..required_size].try_into().unwrap();
+                            let body: Vec<u8> = buf[5..required_size].to_vec();
                             buf.drain(..required_size);
-                            framer_tx.send((type_, body)).await.unwrap();
+                            if framer_tx.send((type_, body)).await.is_err() {
+                                break;
+                            }
                             continue;
                         }
                     }
 
-                    if let Some(msg) = io_rx.recv().await {
-                        buf.extend(msg)
+                    match io_rx.recv().await {
+                        Some(msg) => buf.extend(msg),
+                        None => break,
                     }
                 }
             }
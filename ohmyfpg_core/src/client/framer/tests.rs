@@ -0,0 +1,26 @@
+use crate::client::framer::Framer;
+use crate::client::mock_server::{backend_message, ready_for_query};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time;
+
+#[tokio::test]
+async fn test_write_frames_until_read() {
+    let (stream, mut server) = tokio::io::duplex(64 * 1024);
+    let mut framer = Framer::new(Box::new(stream));
+    let flush = backend_message(b'H', &[]);
+    let sync = backend_message(b'S', &[]);
+    framer.write_raw_frames(flush.clone()).await.unwrap();
+    framer.write_raw_frames(sync.clone()).await.unwrap();
+
+    let mut buf = vec![0; 64 * 1024];
+    let read = time::timeout(Duration::from_millis(50), server.read(&mut buf)).await;
+    assert!(read.is_err());
+
+    // The messages are sent at once before waiting for the response
+    server.write_all(&ready_for_query(b'I')).await.unwrap();
+    let frame = framer.read_frame().await.unwrap();
+    assert!(frame.is_some());
+    let n = server.read(&mut buf).await.unwrap();
+    assert_eq!(&buf[..n], [flush, sync].concat());
+}
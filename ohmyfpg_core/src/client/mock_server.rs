@@ -0,0 +1,104 @@
+//! Backend messages for the tests running a connection against a stand-in server,
+//! over an in-memory stream.
+use crate::client::Connection;
+use crate::server::PgType;
+use std::collections::HashMap;
+use tokio::io::{AsyncReadExt, DuplexStream};
+
+pub fn backend_message(msg_type: u8, body: &[u8]) -> Vec<u8> {
+    let len = (body.len() + 4) as i32;
+    [&[msg_type][..], &len.to_be_bytes(), body].concat()
+}
+
+/// `RowDescription` of `int4` columns with the given names.
+pub fn row_description(names: &[&str]) -> Vec<u8> {
+    let mut body = (names.len() as i16).to_be_bytes().to_vec();
+    for name in names {
+        body.extend_from_slice(name.as_bytes());
+        body.push(0);
+        body.extend_from_slice(&0i32.to_be_bytes());
+        body.extend_from_slice(&0i16.to_be_bytes());
+        body.extend_from_slice(&23u32.to_be_bytes());
+        body.extend_from_slice(&4i16.to_be_bytes());
+        body.extend_from_slice(&(-1i32).to_be_bytes());
+        body.extend_from_slice(&1i16.to_be_bytes());
+    }
+    backend_message(b'T', &body)
+}
+
+/// `DataRow` of `int4` values, `None` being NULL.
+pub fn data_row(values: &[Option<i32>]) -> Vec<u8> {
+    let mut body = (values.len() as i16).to_be_bytes().to_vec();
+    for value in values {
+        match value {
+            Some(value) => {
+                body.extend_from_slice(&4i32.to_be_bytes());
+                body.extend_from_slice(&value.to_be_bytes());
+            }
+            None => body.extend_from_slice(&(-1i32).to_be_bytes()),
+        }
+    }
+    backend_message(b'D', &body)
+}
+
+pub fn command_complete(tag: &str) -> Vec<u8> {
+    backend_message(b'C', format!("{}\0", tag).as_bytes())
+}
+
+pub fn ready_for_query(status: u8) -> Vec<u8> {
+    backend_message(b'Z', &[status])
+}
+
+pub fn error_response(code: &str, message: &str) -> Vec<u8> {
+    let body = format!("SERROR\0C{}\0M{}\0\0", code, message);
+    backend_message(b'E', body.as_bytes())
+}
+
+/// Responses of the extended protocol to a query returning the `int4` column `a`.
+pub fn query_responses(values: &[i32]) -> Vec<u8> {
+    let mut responses = [
+        backend_message(b'1', &[]),
+        backend_message(b't', &0i16.to_be_bytes()),
+        row_description(&["a"]),
+        backend_message(b'2', &[]),
+    ]
+    .concat();
+    for value in values.iter() {
+        responses.extend(data_row(&[Some(*value)]));
+    }
+    responses.extend(command_complete(&format!("SELECT {}", values.len())));
+    responses.extend(ready_for_query(b'I'));
+    responses
+}
+
+/// Returns a connection knowing the `int4` type, and the server end of its stream.
+pub fn connection() -> (Connection, DuplexStream) {
+    let (stream, server) = tokio::io::duplex(64 * 1024);
+    let mut conn = Connection::new(Box::new(stream));
+    let int4 = PgType::new(23, "int4".to_owned(), Some(4));
+    conn.pg_types = Some(HashMap::from([(23, int4)]));
+    (conn, server)
+}
+
+/// Returns the types and bodies of the messages sent by the connection so far.
+pub async fn sent_messages(server: &mut DuplexStream) -> Vec<(u8, Vec<u8>)> {
+    let mut buf = vec![0; 64 * 1024];
+    let n = server.read(&mut buf).await.unwrap();
+    let mut messages = vec![];
+    let mut rest = &buf[..n];
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes(rest[1..5].try_into().unwrap()) as usize;
+        messages.push((rest[0], rest[5..1 + len].to_vec()));
+        rest = &rest[1 + len..];
+    }
+    messages
+}
+
+/// Returns the types of the messages sent by the connection so far.
+pub async fn sent_message_types(server: &mut DuplexStream) -> Vec<u8> {
+    sent_messages(server)
+        .await
+        .into_iter()
+        .map(|(msg_type, _)| msg_type)
+        .collect()
+}
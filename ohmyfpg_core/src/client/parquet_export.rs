@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{ColumnResult, Connection, ExportError, FetchError};
+use arrow_array::{
+    ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array, RecordBatch,
+};
+use parquet::arrow::AsyncArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::fs::File;
+
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ParquetCompression {
+    Uncompressed,
+    Snappy,
+    Zstd,
+}
+
+impl FromStr for ParquetCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uncompressed" => Ok(ParquetCompression::Uncompressed),
+            "snappy" => Ok(ParquetCompression::Snappy),
+            "zstd" => Ok(ParquetCompression::Zstd),
+            _ => Err(format!("Unsupported parquet compression: {}", s)),
+        }
+    }
+}
+
+impl From<ParquetCompression> for Compression {
+    fn from(compression: ParquetCompression) -> Self {
+        match compression {
+            ParquetCompression::Uncompressed => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParquetOptions {
+    /// Number of rows buffered in memory and written as a single row group.
+    pub row_group_size: usize,
+    pub compression: ParquetCompression,
+}
+
+impl ParquetOptions {
+    pub fn new(row_group_size: usize, compression: ParquetCompression) -> Self {
+        ParquetOptions {
+            row_group_size,
+            compression,
+        }
+    }
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        Self::new(DEFAULT_ROW_GROUP_SIZE, ParquetCompression::Snappy)
+    }
+}
+
+impl Connection {
+    /// Streams the result of the query into a parquet file at `path` and returns the
+    /// number of rows written.
+    ///
+    /// At most `options.row_group_size` rows are kept in memory at any time: each
+    /// batch of rows is decoded and written as a row group as soon as it's read.
+    pub async fn fetch_to_parquet<P: AsRef<Path>>(
+        &mut self,
+        query_string: String,
+        path: P,
+        options: ParquetOptions,
+    ) -> Result<u64, ExportError> {
+        if options.row_group_size == 0 {
+            return Err(ExportError::FetchError(FetchError::InvalidParametersError(
+                "row_group_size must be greater than 0".to_owned(),
+            )));
+        }
+        let desc = self.execute_prepared_query(query_string, &[]).await?;
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(options.row_group_size)
+            .set_compression(options.compression.into())
+            .build();
+        let mut file = Some(File::create(path).await?);
+        let mut writer = None;
+        let mut total_rows = 0;
+        loop {
+            let (data_rows_bytes, done) = self.read_data_rows(options.row_group_size).await?;
+            total_rows += data_rows_bytes.len() as u64;
+            // An empty batch is only written when there are no rows at all, so that the
+            // file still carries the schema
+            if !data_rows_bytes.is_empty() || writer.is_none() {
+                let batch = to_record_batch(self.decode_columns(&desc, data_rows_bytes)?)?;
+                if writer.is_none() {
+                    writer = Some(AsyncArrowWriter::try_new(
+                        file.take().unwrap(),
+                        batch.schema(),
+                        Some(props.clone()),
+                    )?);
+                }
+                writer.as_mut().unwrap().write(&batch).await?;
+            }
+
+            if done {
+                break;
+            }
+        }
+
+        writer.unwrap().close().await?;
+        Ok(total_rows)
+    }
+}
+
+fn to_record_batch(columns: Vec<(String, ColumnResult)>) -> Result<RecordBatch, ExportError> {
+    let columns = columns
+        .into_iter()
+        .map(|(name, col_res)| {
+            let array = to_array(&name, col_res)?;
+            Ok((name, array))
+        })
+        .collect::<Result<Vec<(String, ArrayRef)>, ExportError>>()?;
+    Ok(RecordBatch::try_from_iter(columns)?)
+}
+
+fn to_array(name: &str, col_res: ColumnResult) -> Result<ArrayRef, ExportError> {
+    Ok(match col_res.dtype.as_str() {
+        ">i2" => Arc::new(Int16Array::from(col_res.to_native_vec(i16::from_be_bytes))),
+        ">i4" => Arc::new(Int32Array::from(col_res.to_native_vec(i32::from_be_bytes))),
+        ">i8" => Arc::new(Int64Array::from(col_res.to_native_vec(i64::from_be_bytes))),
+        ">f4" => Arc::new(Float32Array::from(
+            col_res.to_native_vec(f32::from_be_bytes),
+        )),
+        ">f8" => Arc::new(Float64Array::from(
+            col_res.to_native_vec(f64::from_be_bytes),
+        )),
+        dtype => {
+            return Err(ExportError::FetchError(FetchError::UnsupportedTypeError(
+                format!("column \"{}\" has dtype {}", name, dtype),
+            )))
+        }
+    })
+}
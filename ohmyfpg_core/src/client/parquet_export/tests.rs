@@ -0,0 +1,46 @@
+use crate::client::parquet_export::{to_record_batch, ParquetCompression, ParquetOptions};
+use crate::client::{ColumnResult, Connection, ExportError, FetchError};
+
+#[test]
+fn test_to_record_batch() {
+    let columns = vec![
+        (
+            "a".to_owned(),
+            ColumnResult::new(vec![0, 1, 0, 2], ">i2".to_owned()),
+        ),
+        (
+            "b".to_owned(),
+            ColumnResult::new(vec![0, 0, 0, 0, 0, 0, 0, 3], ">i4".to_owned()),
+        ),
+    ];
+    let batch = to_record_batch(columns).unwrap();
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.num_columns(), 2);
+
+    let columns = vec![(
+        "c".to_owned(),
+        ColumnResult::new(vec![1, 0], "|b1".to_owned()),
+    )];
+    assert!(matches!(
+        to_record_batch(columns),
+        Err(ExportError::FetchError(FetchError::UnsupportedTypeError(_)))
+    ));
+}
+
+#[tokio::test]
+async fn test_fetch_to_parquet_invalid_row_group_size() {
+    let (stream, _server) = tokio::io::duplex(1024);
+    let mut conn = Connection::new(Box::new(stream));
+    let path = std::env::temp_dir().join(format!("ohmyfpg-{}-empty.parquet", std::process::id()));
+    let options = ParquetOptions::new(0, ParquetCompression::Snappy);
+    let res = conn
+        .fetch_to_parquet("SELECT 1".to_owned(), &path, options)
+        .await;
+    assert!(matches!(
+        res,
+        Err(ExportError::FetchError(FetchError::InvalidParametersError(
+            _
+        )))
+    ));
+    assert!(!path.exists());
+}
@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{
+    connect, quote_identifier, CancelTokens, ConnectionError, FetchError, FetchResult,
+};
+use crate::messages::query::DataRow;
+use crate::messages::DeserializeMessage;
+use futures::future::try_join_all;
+
+/// How a query is split into range-restricted queries that are run concurrently.
+#[derive(Debug, Clone)]
+pub struct PartitionOptions {
+    /// Integer column of the query result whose values are split into ranges.
+    pub column: String,
+    /// Number of partitions, each fetched with its own connection.
+    pub partitions: usize,
+    /// Inclusive minimum and maximum values of the column, computed with `min` and
+    /// `max` when `None`.
+    pub bounds: Option<(i64, i64)>,
+}
+
+impl PartitionOptions {
+    pub fn new(column: String, partitions: usize, bounds: Option<(i64, i64)>) -> Self {
+        PartitionOptions {
+            column,
+            partitions,
+            bounds,
+        }
+    }
+}
+
+/// Fetches the result of the query by splitting it into ranges of the partition
+/// column, each fetched concurrently with its own connection, and decoding all the
+/// rows in parallel into a single result.
+///
+/// The first and last partitions are open-ended and the first one also includes the
+/// `NULL`s, so that every row is fetched exactly once even when the bounds are
+/// narrower than the actual values.
+pub async fn fetch_partitioned(
+    raw_dsn: String,
+    query_string: String,
+    options: PartitionOptions,
+) -> Result<FetchResult, ConnectionError> {
+    fetch_partitioned_with_cancel_tokens(raw_dsn, query_string, options, CancelTokens::new()).await
+}
+
+/// Same as `fetch_partitioned`, the queries of all the connections being cancelled
+/// with `cancel_tokens`.
+pub async fn fetch_partitioned_with_cancel_tokens(
+    raw_dsn: String,
+    query_string: String,
+    options: PartitionOptions,
+    cancel_tokens: CancelTokens,
+) -> Result<FetchResult, ConnectionError> {
+    if options.partitions == 0 {
+        return Err(ConnectionError::from(FetchError::InvalidParametersError(
+            "the number of partitions must be positive".to_owned(),
+        )));
+    }
+    let query_string = query_string.trim_end().trim_end_matches(';').to_owned();
+    let mut connection = connect(raw_dsn.to_owned()).await?;
+    cancel_tokens.add(&connection)?;
+
+    let bounds = match options.bounds {
+        Some(bounds) => Some(bounds),
+        None => {
+            let bounds_query = format!(
+                "SELECT min({col})::int8, max({col})::int8 FROM ({}) AS ohmyfpg_partitioned",
+                query_string,
+                col = quote_identifier(&options.column)
+            );
+            let (_, data_rows) = connection.fetch_raw(bounds_query, &[]).await?;
+            let mut bounds = DataRow::deserialize_body(data_rows.into_iter().next().unwrap())
+                .columns
+                .into_iter()
+                .map(|c| c.map(|v| i64::from_be_bytes(v[..].try_into().unwrap())));
+            match (bounds.next().flatten(), bounds.next().flatten()) {
+                (Some(min), Some(max)) => Some((min, max)),
+                // No rows, or only `NULL`s
+                _ => None,
+            }
+        }
+    };
+    let queries = match bounds {
+        Some((min, max)) => partition_queries(
+            &query_string,
+            &options.column,
+            options.partitions,
+            (min, max),
+        ),
+        None => vec![query_string],
+    };
+
+    let mut connections =
+        try_join_all((1..queries.len()).map(|_| connect(raw_dsn.to_owned()))).await?;
+    for conn in connections.iter() {
+        cancel_tokens.add(conn)?;
+    }
+    connections.insert(0, connection);
+    let results = try_join_all(
+        connections
+            .iter_mut()
+            .zip(queries)
+            .map(|(conn, query)| conn.fetch_raw(query, &[])),
+    )
+    .await?;
+
+    let mut results = results.into_iter();
+    let (desc, mut data_rows) = results.next().unwrap();
+    for (_, partition_rows) in results {
+        data_rows.extend(partition_rows);
+    }
+    Ok(connections[0]
+        .decode_columns(&desc, data_rows)?
+        .into_iter()
+        .collect())
+}
+
+/// Splits the query into `partitions` queries over ranges of `column` of about the
+/// same width. Fewer queries are returned when the range has fewer values.
+fn partition_queries(
+    query_string: &str,
+    column: &str,
+    partitions: usize,
+    (min, max): (i64, i64),
+) -> Vec<String> {
+    let column = quote_identifier(column);
+    let (min, max) = (min as i128, max.max(min) as i128);
+    let partitions = (partitions as i128).min(max - min + 1);
+    // Bounds between partitions, each partition starts at its lower bound included
+    let splits: Vec<i128> = (1..partitions)
+        .map(|i| min + (max - min + 1) * i / partitions)
+        .collect();
+
+    (0..partitions as usize)
+        .map(|i| {
+            let lower = i.checked_sub(1).map(|j| splits[j]);
+            let upper = splits.get(i);
+            let condition = match (lower, upper) {
+                (None, None) => "TRUE".to_owned(),
+                (None, Some(upper)) => format!("{col} < {upper} OR {col} IS NULL", col = column),
+                (Some(lower), None) => format!("{} >= {}", column, lower),
+                (Some(lower), Some(upper)) => {
+                    format!("{col} >= {lower} AND {col} < {upper}", col = column)
+                }
+            };
+            format!(
+                "SELECT * FROM ({}) AS ohmyfpg_partition WHERE {}",
+                query_string, condition
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,24 @@
+use crate::client::partitioned::partition_queries;
+
+#[test]
+fn test_partition_queries() {
+    assert_eq!(
+        partition_queries("SELECT * FROM t", "id", 3, (1, 9)),
+        vec![
+            r#"SELECT * FROM (SELECT * FROM t) AS ohmyfpg_partition WHERE "id" < 4 OR "id" IS NULL"#,
+            r#"SELECT * FROM (SELECT * FROM t) AS ohmyfpg_partition WHERE "id" >= 4 AND "id" < 7"#,
+            r#"SELECT * FROM (SELECT * FROM t) AS ohmyfpg_partition WHERE "id" >= 7"#,
+        ]
+    );
+    assert_eq!(
+        partition_queries("SELECT * FROM t", "id", 4, (5, 5)),
+        vec![r#"SELECT * FROM (SELECT * FROM t) AS ohmyfpg_partition WHERE TRUE"#]
+    );
+    assert_eq!(
+        partition_queries("SELECT * FROM t", "id", 2, (i64::MIN, i64::MAX)),
+        vec![
+            r#"SELECT * FROM (SELECT * FROM t) AS ohmyfpg_partition WHERE "id" < 0 OR "id" IS NULL"#,
+            r#"SELECT * FROM (SELECT * FROM t) AS ohmyfpg_partition WHERE "id" >= 0"#,
+        ]
+    );
+}
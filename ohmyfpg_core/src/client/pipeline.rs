@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{Connection, FetchError, FetchResult};
+use crate::messages::query::{Bind, Describe, Execute, Format, Parse, RowDescription, Sync};
+use crate::messages::{DeserializeMessage, RawTypedBackendMessage, SerializeMessage};
+use crate::types::ToSql;
+
+/// Queries sent to the server at once and executed in order, so that running them
+/// takes a single round trip.
+///
+/// Queries run in the same implicit transaction: if one of them fails, the following
+/// ones are skipped and the whole pipeline is rolled back.
+#[derive(Debug, Default)]
+pub struct Pipeline {
+    /// Serialized messages of all the queries.
+    messages: Vec<u8>,
+    len: usize,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the query with its parameters, which are encoded right away.
+    pub fn push(&mut self, query_string: String, params: &[&dyn ToSql]) {
+        let param_types = params.iter().map(|p| p.type_oid()).collect();
+        let param_values = params.iter().map(|p| p.to_sql()).collect();
+        self.messages.extend(
+            Parse::new_with_statement("".to_owned(), query_string, param_types).serialize(),
+        );
+        self.messages.extend(
+            Bind::new_with_statement("".to_owned(), "".to_owned(), Format::Binary, param_values)
+                .serialize(),
+        );
+        self.messages.extend(Describe::default().serialize());
+        self.messages.extend(Execute::default().serialize());
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Connection {
+    /// Sends all the queries of the pipeline followed by a single `Sync`, and returns
+    /// the result of each query in the same order.
+    ///
+    /// Queries use the unnamed statement, so they aren't cached. If a query fails, its
+    /// error is returned once the server is ready for the next query.
+    pub async fn fetch_pipeline(
+        &mut self,
+        pipeline: Pipeline,
+    ) -> Result<Vec<FetchResult>, FetchError> {
+        let mut messages = pipeline.messages;
+        messages.extend(Sync::default().serialize());
+        self.write_pending_rollbacks().await?;
+        self.pending_responses += 1;
+        self.unsynced = false;
+        self.start_query_timeout();
+        self.framer.write_raw_frames(messages).await?;
+
+        let mut results = Vec::with_capacity(pipeline.len);
+        let mut desc = RowDescription::new(vec![]);
+        let mut data_rows = vec![];
+        let mut error = None;
+        loop {
+            match self.read_raw_typed_message().await? {
+                RawTypedBackendMessage::DataRow(body) => data_rows.push(body),
+                RawTypedBackendMessage::RowDescription(body) => {
+                    desc = RowDescription::deserialize_body(body);
+                }
+                // Statements that don't return rows have no columns
+                RawTypedBackendMessage::NoData(_) => desc = RowDescription::new(vec![]),
+                RawTypedBackendMessage::CommandComplete(_)
+                | RawTypedBackendMessage::EmptyQueryResponse(_) => {
+                    let rows = std::mem::take(&mut data_rows);
+                    match self.decode_columns(&desc, rows) {
+                        Ok(columns) => results.push(columns.into_iter().collect()),
+                        // The remaining messages are still read
+                        Err(err) => {
+                            error.get_or_insert(err);
+                        }
+                    }
+                }
+                RawTypedBackendMessage::ParseComplete(_)
+                | RawTypedBackendMessage::BindComplete(_)
+                | RawTypedBackendMessage::NoticeResponse(_)
+                | RawTypedBackendMessage::ParameterStatus(_) => {}
+                RawTypedBackendMessage::ReadyForQuery(_) => break,
+                msg => {
+                    // The server skips every message until the `Sync` after an error
+                    error.get_or_insert(FetchError::UnexpectedMessageError(msg.parse()));
+                }
+            }
+        }
+
+        error.map_or(Ok(results), Err)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::client::mock_server::{
+    backend_message, command_complete, connection, data_row, error_response, query_responses,
+    ready_for_query, row_description, sent_message_types,
+};
+use crate::client::{FetchError, Pipeline};
+use crate::messages::BackendMessage;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Pipeline of a query returning rows, and of one that doesn't.
+fn pipeline() -> Pipeline {
+    let mut pipeline = Pipeline::new();
+    pipeline.push("SELECT $1::int4 AS a".to_owned(), &[&1i32]);
+    pipeline.push("INSERT INTO t VALUES (2)".to_owned(), &[]);
+    pipeline
+}
+
+fn pipeline_responses() -> Vec<u8> {
+    [
+        backend_message(b'1', &[]),
+        backend_message(b'2', &[]),
+        row_description(&["a"]),
+        data_row(&[Some(1)]),
+        command_complete("SELECT 1"),
+        backend_message(b'1', &[]),
+        backend_message(b'2', &[]),
+        backend_message(b'n', &[]),
+        command_complete("INSERT 0 1"),
+        ready_for_query(b'I'),
+    ]
+    .concat()
+}
+
+#[test]
+fn test_push() {
+    let mut pipeline = Pipeline::new();
+    assert!(pipeline.is_empty());
+    pipeline.push("SELECT $1::int4".to_owned(), &[&1i32]);
+    pipeline.push("SELECT 2".to_owned(), &[]);
+    assert_eq!(pipeline.len(), 2);
+
+    let mut msg_types = vec![];
+    let mut rest = &pipeline.messages[..];
+    while !rest.is_empty() {
+        msg_types.push(rest[0]);
+        let len = u32::from_be_bytes(rest[1..5].try_into().unwrap()) as usize;
+        rest = &rest[1 + len..];
+    }
+    assert_eq!(msg_types, b"PBDEPBDE");
+}
+
+#[tokio::test]
+async fn test_fetch_pipeline() {
+    let (mut conn, mut server) = connection();
+    server.write_all(&pipeline_responses()).await.unwrap();
+    conn.set_query_timeout(Some(Duration::from_secs(10)));
+
+    let results = conn.fetch_pipeline(pipeline()).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["a"].bytes, 1i32.to_be_bytes());
+    assert!(results[1].is_empty());
+    // A single `Sync` for all the queries
+    assert_eq!(sent_message_types(&mut server).await, b"PBDEPBDES");
+    // The request is over once its `ReadyForQuery` is read
+    assert_eq!(conn.pending_responses, 0);
+    assert!(!conn.unsynced);
+    assert!(conn.deadline.is_none());
+}
+
+#[tokio::test]
+async fn test_fetch_pipeline_error() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        backend_message(b'1', &[]),
+        backend_message(b'2', &[]),
+        row_description(&["a"]),
+        data_row(&[Some(1)]),
+        command_complete("SELECT 1"),
+        // The server skips the second query
+        error_response("23505", "duplicate key value"),
+        ready_for_query(b'I'),
+    ];
+    server
+        .write_all(&[responses.concat(), query_responses(&[4])].concat())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        conn.fetch_pipeline(pipeline()).await,
+        Err(FetchError::UnexpectedMessageError(
+            BackendMessage::ErrorResponse(_)
+        ))
+    ));
+    // The error is returned once the single `Sync` is answered, so the connection
+    // is ready for the next query
+    assert_eq!(conn.pending_responses, 0);
+    let res = conn.fetch("SELECT 4".to_owned(), &[]).await.unwrap();
+    assert_eq!(res["a"].bytes, 4i32.to_be_bytes());
+}
+
+#[tokio::test]
+async fn test_fetch_pipeline_after_skipped_response() {
+    let (mut conn, mut server) = connection();
+    // E.g. a request that timed out before its `Sync` was sent
+    conn.unsynced = true;
+    conn.skip_current_response();
+    server
+        .write_all(&[ready_for_query(b'I'), pipeline_responses()].concat())
+        .await
+        .unwrap();
+
+    // The skipped request is ended first, and its response isn't mistaken for the
+    // one of the pipeline
+    let results = conn.fetch_pipeline(pipeline()).await.unwrap();
+    assert_eq!(results[0]["a"].bytes, 1i32.to_be_bytes());
+    assert_eq!(sent_message_types(&mut server).await, b"SPBDEPBDES");
+    assert_eq!(conn.pending_responses, 0);
+    assert_eq!(conn.unread_responses, 0);
+}
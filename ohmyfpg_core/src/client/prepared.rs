@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{Connection, FetchError, FetchResult};
+use crate::messages::query::{
+    Bind, Close, Describe, Execute, Flush, Format, Parse, RowDescription, Target,
+};
+use crate::messages::BackendMessage;
+use crate::types::{is_binary_compatible, ToSql};
+
+/// Named prepared statement whose parameters types and result columns are known
+/// before it's executed.
+#[derive(Debug)]
+pub struct PreparedStatement {
+    name: String,
+    param_types: Vec<u32>,
+    desc: RowDescription,
+}
+
+impl PreparedStatement {
+    /// OIDs of the parameters types, as inferred by the server.
+    pub fn param_types(&self) -> &[u32] {
+        &self.param_types
+    }
+
+    pub fn desc(&self) -> &RowDescription {
+        &self.desc
+    }
+}
+
+impl Connection {
+    /// Prepares the query as a named statement, that can then be executed any number
+    /// of times with `fetch_prepared` until it's closed with `close_prepared`.
+    ///
+    /// The types of the parameters are inferred by the server.
+    pub async fn prepare(&mut self, query_string: String) -> Result<PreparedStatement, FetchError> {
+        let name = self.statement_cache.next_name();
+        self.write_message(Parse::new_with_statement(
+            name.to_owned(),
+            query_string,
+            vec![],
+        ))
+        .await?;
+        self.write_message(Describe::new(Target::Statement(name.to_owned())))
+            .await?;
+        self.write_message(Flush::default()).await?;
+
+        let res = async {
+            match self.read_message().await? {
+                BackendMessage::ParseComplete(_) => {}
+                msg => return Err(FetchError::UnexpectedMessageError(msg)),
+            }
+            let param_types = match self.read_message().await? {
+                BackendMessage::ParameterDescription(param_desc) => param_desc.param_types,
+                msg => return Err(FetchError::UnexpectedMessageError(msg)),
+            };
+            match self.read_message().await? {
+                BackendMessage::RowDescription(desc) => Ok((param_types, desc)),
+                BackendMessage::NoData(_) => Ok((param_types, RowDescription::new(vec![]))),
+                msg => Err(FetchError::UnexpectedMessageError(msg)),
+            }
+        }
+        .await;
+
+        match res {
+            Ok((param_types, desc)) => Ok(PreparedStatement {
+                name,
+                param_types,
+                desc,
+            }),
+            Err(err) => {
+                if let FetchError::UnexpectedMessageError(_) = err {
+                    self.sync().await?;
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Executes the prepared statement with the given parameters, whose types must
+    /// match the ones of the statement.
+    pub async fn fetch_prepared(
+        &mut self,
+        stmt: &PreparedStatement,
+        params: &[&dyn ToSql],
+    ) -> Result<FetchResult, FetchError> {
+        if params.len() != stmt.param_types.len() {
+            return Err(FetchError::InvalidParametersError(format!(
+                "expected {} parameters, got {}",
+                stmt.param_types.len(),
+                params.len()
+            )));
+        }
+        for (i, (param, param_type)) in params.iter().zip(&stmt.param_types).enumerate() {
+            if !is_binary_compatible(param.type_oid(), *param_type) {
+                return Err(FetchError::InvalidParametersError(format!(
+                    "parameter ${} has type OID {}, expected {}",
+                    i + 1,
+                    param.type_oid(),
+                    param_type
+                )));
+            }
+        }
+
+        self.write_message(Bind::new_with_statement(
+            "".to_owned(),
+            stmt.name.to_owned(),
+            Format::Binary,
+            params.iter().map(|p| p.to_sql()).collect(),
+        ))
+        .await?;
+        self.write_message(Execute::default()).await?;
+        self.write_message(Flush::default()).await?;
+        match self.read_message().await? {
+            BackendMessage::BindComplete(_) => {}
+            msg => {
+                self.sync().await?;
+                return Err(FetchError::UnexpectedMessageError(msg));
+            }
+        }
+
+        let (data_rows_bytes, _) = self.read_data_rows(usize::MAX).await?;
+        Ok(self
+            .decode_columns(&stmt.desc, data_rows_bytes)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Closes the prepared statement.
+    pub async fn close_prepared(&mut self, stmt: PreparedStatement) -> Result<(), FetchError> {
+        self.write_message(Close::new(Target::Statement(stmt.name)))
+            .await?;
+        self.sync().await
+    }
+}
@@ -0,0 +1,118 @@
+use crate::client::mock_server::{
+    backend_message, command_complete, connection, data_row, error_response, ready_for_query,
+    row_description, sent_message_types,
+};
+use crate::client::FetchError;
+use crate::messages::BackendMessage;
+use tokio::io::AsyncWriteExt;
+
+fn parameter_description(param_types: &[u32]) -> Vec<u8> {
+    let mut body = (param_types.len() as i16).to_be_bytes().to_vec();
+    for param_type in param_types {
+        body.extend_from_slice(&param_type.to_be_bytes());
+    }
+    backend_message(b't', &body)
+}
+
+#[tokio::test]
+async fn test_prepare() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        backend_message(b'1', &[]),
+        parameter_description(&[23]),
+        row_description(&["a"]),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    let stmt = conn
+        .prepare("SELECT a FROM t WHERE a > $1".to_owned())
+        .await
+        .unwrap();
+    // The type of the parameter is inferred by the server
+    assert_eq!(stmt.param_types(), [23]);
+    assert_eq!(stmt.desc().fields[0].name, "a");
+    assert_eq!(sent_message_types(&mut server).await, b"PDH");
+
+    for value in [2, 3] {
+        let responses = [
+            backend_message(b'2', &[]),
+            data_row(&[Some(value)]),
+            command_complete("SELECT 1"),
+            ready_for_query(b'I'),
+        ];
+        server.write_all(&responses.concat()).await.unwrap();
+        let res = conn.fetch_prepared(&stmt, &[&1i32]).await.unwrap();
+        assert_eq!(res["a"].bytes, value.to_be_bytes());
+        // Neither parsed nor described again
+        assert_eq!(sent_message_types(&mut server).await, b"BEHS");
+    }
+
+    let responses = [backend_message(b'3', &[]), ready_for_query(b'I')];
+    server.write_all(&responses.concat()).await.unwrap();
+    conn.close_prepared(stmt).await.unwrap();
+    assert_eq!(sent_message_types(&mut server).await, b"CS");
+}
+
+#[tokio::test]
+async fn test_prepare_no_data() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        backend_message(b'1', &[]),
+        parameter_description(&[]),
+        backend_message(b'n', &[]),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    let stmt = conn.prepare("DELETE FROM t".to_owned()).await.unwrap();
+    assert!(stmt.param_types().is_empty());
+    assert!(stmt.desc().fields.is_empty());
+}
+
+#[tokio::test]
+async fn test_prepare_error() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        error_response("42P01", "relation \"t\" does not exist"),
+        ready_for_query(b'I'),
+        backend_message(b'1', &[]),
+        parameter_description(&[]),
+        backend_message(b'n', &[]),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    assert!(matches!(
+        conn.prepare("DELETE FROM t".to_owned()).await,
+        Err(FetchError::UnexpectedMessageError(
+            BackendMessage::ErrorResponse(_)
+        ))
+    ));
+    // The error is followed by a `Sync`, so that the server handles the next messages
+    assert_eq!(sent_message_types(&mut server).await, b"PDHS");
+    assert!(conn.prepare("SELECT".to_owned()).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_fetch_prepared_invalid_params() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        backend_message(b'1', &[]),
+        parameter_description(&[23]),
+        backend_message(b'n', &[]),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+    let stmt = conn
+        .prepare("DELETE FROM t WHERE a = $1".to_owned())
+        .await
+        .unwrap();
+
+    // Checked before sending anything
+    assert!(matches!(
+        conn.fetch_prepared(&stmt, &[]).await,
+        Err(FetchError::InvalidParametersError(_))
+    ));
+    assert!(matches!(
+        conn.fetch_prepared(&stmt, &[&"1"]).await,
+        Err(FetchError::InvalidParametersError(_))
+    ));
+    assert_eq!(sent_message_types(&mut server).await, b"PDH");
+}
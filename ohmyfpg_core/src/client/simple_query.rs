@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{CommandTag, Connection, FetchError};
+use crate::messages::copy::CopyFail;
+use crate::messages::query::{CommandComplete, DataRow, Query, RowDescription};
+use crate::messages::{DeserializeMessage, RawTypedBackendMessage};
+
+const COPY_NOT_SUPPORTED: &str = "COPY is not supported by simple_query";
+
+/// Result of one of the statements of a simple query.
+#[derive(Debug, Default)]
+pub struct SimpleQueryResult {
+    /// `None` for statements that don't return rows.
+    pub desc: Option<RowDescription>,
+    /// Values in text format, `None` stands for `NULL`.
+    pub rows: Vec<Vec<Option<String>>>,
+    /// `None` for an empty statement.
+    pub tag: Option<CommandTag>,
+}
+
+impl Connection {
+    /// Runs a script of one or more statements separated by semicolons using the
+    /// simple query protocol, and returns the result of each statement.
+    ///
+    /// Parameters aren't supported and values are returned in text format. Unless the
+    /// script contains explicit transaction control statements, it runs as a single
+    /// transaction, and the first failing statement aborts the whole script. `COPY`
+    /// statements aren't supported, the ones from `STDIN` fail and the rows of the
+    /// ones to `STDOUT` are skipped.
+    pub async fn simple_query(
+        &mut self,
+        query_string: String,
+    ) -> Result<Vec<SimpleQueryResult>, FetchError> {
+        self.write_message(Query::new(query_string)).await?;
+        let mut results = vec![];
+        let mut current = SimpleQueryResult::default();
+        let mut error = None;
+        loop {
+            match self.read_raw_typed_message().await? {
+                RawTypedBackendMessage::RowDescription(body) => {
+                    current.desc = Some(RowDescription::deserialize_body(body));
+                }
+                RawTypedBackendMessage::DataRow(body) => {
+                    let row = DataRow::deserialize_body(body)
+                        .columns
+                        .into_iter()
+                        .map(|c| c.map(|v| String::from_utf8_lossy(&v).into_owned()))
+                        .collect();
+                    current.rows.push(row);
+                }
+                RawTypedBackendMessage::CommandComplete(body) => {
+                    let tag = CommandComplete::deserialize_body(body).tag;
+                    current.tag = Some(CommandTag::parse(&tag));
+                    results.push(std::mem::take(&mut current));
+                }
+                RawTypedBackendMessage::EmptyQueryResponse(_) => {
+                    results.push(std::mem::take(&mut current));
+                }
+                // Otherwise the server waits for the data to copy. Nothing else can be
+                // sent meanwhile, such as pending rollbacks.
+                RawTypedBackendMessage::CopyInResponse(_) => {
+                    self.write_frame(CopyFail::new(COPY_NOT_SUPPORTED.to_owned()))
+                        .await?;
+                    error.get_or_insert(FetchError::InvalidStateError(
+                        COPY_NOT_SUPPORTED.to_owned(),
+                    ));
+                }
+                RawTypedBackendMessage::CopyOutResponse(_) => {
+                    error.get_or_insert(FetchError::InvalidStateError(
+                        COPY_NOT_SUPPORTED.to_owned(),
+                    ));
+                }
+                RawTypedBackendMessage::CopyData(_)
+                | RawTypedBackendMessage::CopyDone(_)
+                | RawTypedBackendMessage::NoticeResponse(_)
+                | RawTypedBackendMessage::ParameterStatus(_) => {}
+                RawTypedBackendMessage::ReadyForQuery(_) => break,
+                msg => {
+                    // The server is ready for the next query only after `ReadyForQuery`
+                    error.get_or_insert(FetchError::UnexpectedMessageError(msg.parse()));
+                }
+            }
+        }
+
+        error.map_or(Ok(results), Err)
+    }
+}
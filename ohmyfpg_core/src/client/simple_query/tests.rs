@@ -0,0 +1,111 @@
+use crate::client::mock_server::{
+    backend_message, command_complete, connection, data_row, error_response, ready_for_query,
+    row_description, sent_message_types,
+};
+use crate::client::{CommandTag, FetchError};
+use crate::messages::BackendMessage;
+use tokio::io::AsyncWriteExt;
+
+#[tokio::test]
+async fn test_simple_query() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        command_complete("CREATE TABLE"),
+        backend_message(b'I', &[]),
+        row_description(&["a"]),
+        data_row(&[Some(1)]),
+        data_row(&[None]),
+        command_complete("SELECT 2"),
+        ready_for_query(b'I'),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    let results = conn
+        .simple_query("CREATE TABLE t (a int4); ; SELECT a FROM t".to_owned())
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    assert!(results[0].desc.is_none());
+    assert_eq!(
+        results[0].tag,
+        Some(CommandTag::new("CREATE TABLE".to_owned(), None))
+    );
+    // The empty statement has no tag
+    assert!(results[1].tag.is_none());
+    assert_eq!(results[2].desc.as_ref().unwrap().fields[0].name, "a");
+    // In text format
+    assert_eq!(
+        results[2].rows,
+        vec![
+            vec![Some(
+                String::from_utf8_lossy(&1i32.to_be_bytes()).into_owned()
+            )],
+            vec![None]
+        ]
+    );
+    assert_eq!(
+        results[2].tag,
+        Some(CommandTag::new("SELECT".to_owned(), Some(2)))
+    );
+}
+
+#[tokio::test]
+async fn test_simple_query_error() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        command_complete("INSERT 0 1"),
+        error_response("22012", "division by zero"),
+        ready_for_query(b'I'),
+        command_complete("SELECT 0"),
+        ready_for_query(b'I'),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    // The statements after the failing one aren't run
+    assert!(matches!(
+        conn.simple_query("INSERT INTO t VALUES (1); SELECT 1 / 0; SELECT 1".to_owned())
+            .await,
+        Err(FetchError::UnexpectedMessageError(
+            BackendMessage::ErrorResponse(_)
+        ))
+    ));
+    // The connection is ready for the next query
+    let results = conn.simple_query("SELECT".to_owned()).await.unwrap();
+    assert_eq!(results.len(), 1);
+}
+
+#[tokio::test]
+async fn test_simple_query_copy() {
+    let (mut conn, mut server) = connection();
+    let copy_out_responses = [
+        backend_message(b'H', &[0, 0, 1, 0, 0]),
+        backend_message(b'd', b"1\n"),
+        backend_message(b'c', &[]),
+        command_complete("COPY 1"),
+        ready_for_query(b'I'),
+    ];
+    server
+        .write_all(&copy_out_responses.concat())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        conn.simple_query("COPY t TO STDOUT".to_owned()).await,
+        Err(FetchError::InvalidStateError(_))
+    ));
+    assert_eq!(sent_message_types(&mut server).await, b"Q");
+
+    let copy_in_responses = [
+        backend_message(b'G', &[0, 0, 1, 0, 0]),
+        error_response("57014", "COPY from stdin failed"),
+        ready_for_query(b'I'),
+    ];
+    server.write_all(&copy_in_responses.concat()).await.unwrap();
+
+    // The copy is aborted, instead of the server waiting for the data
+    assert!(matches!(
+        conn.simple_query("COPY t FROM STDIN".to_owned()).await,
+        Err(FetchError::InvalidStateError(_))
+    ));
+    assert_eq!(sent_message_types(&mut server).await, b"Qf");
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests;
+use crate::messages::query::RowDescription;
+use std::collections::HashMap;
+
+pub const DEFAULT_STATEMENT_CACHE_SIZE: usize = 100;
+
+#[derive(Debug)]
+pub struct CachedStatement {
+    pub name: String,
+    pub param_types: Vec<u32>,
+    pub desc: RowDescription,
+    last_used: u64,
+}
+
+/// Least recently used cache of the named prepared statements of a connection, keyed
+/// by query text.
+#[derive(Debug)]
+pub struct StatementCache {
+    capacity: usize,
+    statements: HashMap<String, CachedStatement>,
+    clock: u64,
+    next_statement_id: u32,
+}
+
+impl StatementCache {
+    pub fn new(capacity: usize) -> Self {
+        StatementCache {
+            capacity,
+            statements: HashMap::new(),
+            clock: 0,
+            next_statement_id: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Returns the statement prepared for the query with the same parameters types,
+    /// marking it as the most recently used.
+    pub fn get(&mut self, query_string: &str, param_types: &[u32]) -> Option<&CachedStatement> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.statements
+            .get_mut(query_string)
+            .filter(|stmt| stmt.param_types == param_types)
+            .map(|stmt| {
+                stmt.last_used = clock;
+                &*stmt
+            })
+    }
+
+    /// Returns a new unique statement name.
+    pub fn next_name(&mut self) -> String {
+        self.next_statement_id += 1;
+        format!("ohmyfpg_stmt_{}", self.next_statement_id)
+    }
+
+    /// Removes the statements that no longer fit, so that a new one can be inserted,
+    /// and returns their names so that they can be closed. A statement prepared for
+    /// the same query with different parameters types is replaced too.
+    pub fn make_room(&mut self, query_string: &str) -> Vec<String> {
+        let mut evicted: Vec<String> = self
+            .statements
+            .remove(query_string)
+            .map(|stmt| stmt.name)
+            .into_iter()
+            .collect();
+        evicted.extend(self.shrink_to(self.capacity.saturating_sub(1)));
+        evicted
+    }
+
+    pub fn insert(
+        &mut self,
+        query_string: String,
+        name: String,
+        param_types: Vec<u32>,
+        desc: RowDescription,
+    ) {
+        self.clock += 1;
+        let stmt = CachedStatement {
+            name,
+            param_types,
+            desc,
+            last_used: self.clock,
+        };
+        self.statements.insert(query_string, stmt);
+    }
+
+    pub fn remove(&mut self, query_string: &str) -> Option<CachedStatement> {
+        self.statements.remove(query_string)
+    }
+
+    /// Changes the capacity and returns the names of the evicted statements.
+    pub fn resize(&mut self, capacity: usize) -> Vec<String> {
+        self.capacity = capacity;
+        self.shrink_to(capacity)
+    }
+
+    fn shrink_to(&mut self, len: usize) -> Vec<String> {
+        let mut evicted = vec![];
+        while self.statements.len() > len {
+            let lru_query = self
+                .statements
+                .iter()
+                .min_by_key(|(_, stmt)| stmt.last_used)
+                .map(|(query_string, _)| query_string.to_owned())
+                .unwrap();
+            evicted.push(self.statements.remove(&lru_query).unwrap().name);
+        }
+        evicted
+    }
+}
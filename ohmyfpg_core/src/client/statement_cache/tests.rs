@@ -0,0 +1,22 @@
+use crate::client::statement_cache::StatementCache;
+use crate::messages::query::RowDescription;
+
+#[test]
+fn test_lru_eviction() {
+    let mut cache = StatementCache::new(2);
+    for query in ["q1", "q2"] {
+        assert!(cache.make_room(query).is_empty());
+        let name = cache.next_name();
+        cache.insert(query.to_owned(), name, vec![], RowDescription::new(vec![]));
+    }
+    assert_eq!(cache.get("q1", &[]).unwrap().name, "ohmyfpg_stmt_1");
+    assert!(cache.get("q1", &[23]).is_none());
+
+    // `q2` is the least recently used one
+    assert_eq!(cache.make_room("q3"), vec!["ohmyfpg_stmt_2"]);
+    assert!(cache.get("q2", &[]).is_none());
+    // A statement for the same query is always replaced
+    assert_eq!(cache.make_room("q1"), vec!["ohmyfpg_stmt_1"]);
+    assert!(cache.resize(0).is_empty());
+    assert!(!cache.is_enabled());
+}
@@ -0,0 +1,34 @@
+use crate::client::mock_server::{
+    backend_message, command_complete, connection, data_row, query_responses, ready_for_query,
+    row_description,
+};
+use crate::client::FetchError;
+use tokio::io::AsyncWriteExt;
+
+#[tokio::test]
+async fn test_fetch_null_value() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        backend_message(b'1', &[]),
+        backend_message(b't', &0i16.to_be_bytes()),
+        row_description(&["a", "b"]),
+        backend_message(b'2', &[]),
+        data_row(&[Some(1), Some(2)]),
+        data_row(&[Some(3), None]),
+        command_complete("SELECT 2"),
+        ready_for_query(b'I'),
+    ]
+    .concat();
+    server
+        .write_all(&[responses, query_responses(&[4])].concat())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        conn.fetch("SELECT a, b FROM t".to_owned(), &[]).await,
+        Err(FetchError::NullValueError(msg)) if msg == "column \"b\" has a NULL value"
+    ));
+    // The whole response was read, so the connection can run the next query
+    let res = conn.fetch("SELECT 4".to_owned(), &[]).await.unwrap();
+    assert_eq!(res["a"].bytes, 4i32.to_be_bytes());
+}
@@ -0,0 +1,352 @@
+#[cfg(test)]
+mod tests;
+use crate::client::error::{ConnectionError, TlsError};
+use crate::client::framer::Stream;
+use crate::messages::startup::SslRequest;
+use crate::messages::SerializeMessage;
+use std::fs::File;
+use std::io::BufReader;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::client::WebPkiServerVerifier;
+use tokio_rustls::rustls::crypto::{
+    ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider,
+};
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{
+    self, CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+
+/// Same as libpq's `sslmode`, except for `allow`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SslMode {
+    Disable,
+    /// TLS when the server supports it, without verifying its certificate. The
+    /// connection falls back to plaintext when the handshake fails.
+    #[default]
+    Prefer,
+    /// TLS without verifying the server certificate, unless a root certificate is
+    /// available, in which case it's the same as `VerifyCa`.
+    Require,
+    /// TLS with a server certificate signed by a trusted authority.
+    VerifyCa,
+    /// Same as `VerifyCa`, and the certificate must also match the host.
+    VerifyFull,
+}
+
+impl FromStr for SslMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disable" => Ok(SslMode::Disable),
+            "prefer" => Ok(SslMode::Prefer),
+            "require" => Ok(SslMode::Require),
+            "verify-ca" => Ok(SslMode::VerifyCa),
+            "verify-full" => Ok(SslMode::VerifyFull),
+            _ => Err(format!("Unsupported sslmode: {}", s)),
+        }
+    }
+}
+
+/// Same as libpq's `channel_binding`, which binds SCRAM authentication to the TLS
+/// connection so that a man-in-the-middle can't relay it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelBinding {
+    Disable,
+    /// Channel binding when the connection uses TLS and the server supports it.
+    #[default]
+    Prefer,
+    /// Fails to authenticate without channel binding.
+    Require,
+}
+
+impl FromStr for ChannelBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disable" => Ok(ChannelBinding::Disable),
+            "prefer" => Ok(ChannelBinding::Prefer),
+            "require" => Ok(ChannelBinding::Require),
+            _ => Err(format!("Unsupported channel_binding: {}", s)),
+        }
+    }
+}
+
+/// TLS options, set with the `sslmode`, `sslrootcert`, `sslcert`, `sslkey` and
+/// `channel_binding` parameters of the DSN.
+///
+/// Certificates default to the files in `~/.postgresql/` used by libpq, which are
+/// ignored when missing.
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    pub mode: SslMode,
+    /// PEM file of the authorities trusted to sign the server certificate.
+    pub root_cert: Option<PathBuf>,
+    /// PEM file of the client certificate.
+    pub cert: Option<PathBuf>,
+    /// PEM file of the client certificate key.
+    pub key: Option<PathBuf>,
+    pub channel_binding: ChannelBinding,
+}
+
+impl TlsOptions {
+    pub fn new(
+        mode: SslMode,
+        root_cert: Option<PathBuf>,
+        cert: Option<PathBuf>,
+        key: Option<PathBuf>,
+        channel_binding: ChannelBinding,
+    ) -> Self {
+        TlsOptions {
+            mode,
+            root_cert,
+            cert,
+            key,
+            channel_binding,
+        }
+    }
+}
+
+/// Asks the server to switch to TLS and performs the handshake, as the options
+/// require. The stream is returned as is when TLS is disabled, or when it's
+/// preferred but the server doesn't support it.
+///
+/// The server certificate is returned along with the stream when using TLS.
+pub async fn negotiate(
+    mut stream: TcpStream,
+    host: &str,
+    options: &TlsOptions,
+) -> Result<(Box<dyn Stream>, Option<Vec<u8>>), ConnectionError> {
+    if options.mode == SslMode::Disable {
+        return Ok((Box::new(stream), None));
+    }
+    // Files are checked first, so that a misconfiguration doesn't go unnoticed
+    let config = client_config(options)?;
+
+    stream.write_all(&SslRequest::default().serialize()).await?;
+    // Exactly one byte is read, as anything the server sends before the handshake
+    // can't be trusted
+    match stream.read_u8().await? {
+        b'S' => {}
+        _ if options.mode == SslMode::Prefer => return Ok((Box::new(stream), None)),
+        _ => return Err(ConnectionError::from(TlsError::NotSupported)),
+    }
+
+    let server_name = match ServerName::try_from(host.to_owned()) {
+        Ok(server_name) => server_name,
+        Err(_) if options.mode == SslMode::VerifyFull => {
+            return Err(ConnectionError::from(TlsError::InvalidServerName(
+                host.to_owned(),
+            )));
+        }
+        // The name is only used for verification and SNI, which are both skipped
+        Err(_) => ServerName::IpAddress(IpAddr::V4(Ipv4Addr::UNSPECIFIED).into()),
+    };
+    let tls_stream = TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .map_err(TlsError::HandshakeError)?;
+    let server_cert = tls_stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .map(|cert| cert.to_vec());
+    Ok((Box::new(tls_stream), server_cert))
+}
+
+fn client_config(options: &TlsOptions) -> Result<ClientConfig, TlsError> {
+    let provider = Arc::new(ring::default_provider());
+    let root_cert = cert_file(&options.root_cert, "root.crt")?;
+    let builder = ClientConfig::builder_with_provider(provider.to_owned())
+        .with_safe_default_protocol_versions()
+        .map_err(|err| TlsError::ConfigError(err.to_string()))?;
+
+    let builder = match (options.mode, root_cert) {
+        (SslMode::VerifyCa | SslMode::VerifyFull, None) => {
+            return Err(TlsError::InvalidFile(
+                options
+                    .root_cert
+                    .to_owned()
+                    .or_else(|| default_cert_path("root.crt"))
+                    .unwrap_or_default(),
+                "a root certificate is required to verify the server".to_owned(),
+            ));
+        }
+        (SslMode::VerifyFull, Some(path)) => builder.with_root_certificates(root_store(&path)?),
+        (SslMode::Require | SslMode::VerifyCa, Some(path)) => {
+            let verifier =
+                WebPkiServerVerifier::builder_with_provider(Arc::new(root_store(&path)?), provider)
+                    .build()
+                    .map_err(|err| TlsError::ConfigError(err.to_string()))?;
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoHostnameVerifier(verifier)))
+        }
+        _ => builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerifier(provider))),
+    };
+
+    let cert = cert_file(&options.cert, "postgresql.crt")?;
+    let key = cert_file(&options.key, "postgresql.key")?;
+    match (cert, key) {
+        (Some(cert_path), Some(key_path)) => {
+            let certs = read_certs(&cert_path)?;
+            let file = File::open(&key_path).map_err(|err| invalid_file(&key_path, err))?;
+            let key: PrivateKeyDer = rustls_pemfile::private_key(&mut BufReader::new(file))
+                .map_err(|err| invalid_file(&key_path, err))?
+                .ok_or_else(|| invalid_file(&key_path, "no private key found"))?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .map_err(|err| invalid_file(&key_path, err))
+        }
+        (Some(cert_path), None) => Err(invalid_file(&cert_path, "no matching key file")),
+        _ => Ok(builder.with_no_client_auth()),
+    }
+}
+
+/// Returns the path of the certificate file if it exists, defaulting to the file
+/// used by libpq. Unlike the default, a given file must exist.
+fn cert_file(path: &Option<PathBuf>, default_name: &str) -> Result<Option<PathBuf>, TlsError> {
+    match path {
+        Some(path) if path.is_file() => Ok(Some(path.to_owned())),
+        Some(path) => Err(invalid_file(path, "file not found")),
+        None => Ok(default_cert_path(default_name).filter(|path| path.is_file())),
+    }
+}
+
+fn default_cert_path(name: &str) -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| Path::new(&home).join(".postgresql").join(name))
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let file = File::open(path).map_err(|err| invalid_file(path, err))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| invalid_file(path, err))?;
+    if certs.is_empty() {
+        return Err(invalid_file(path, "no certificate found"));
+    }
+    Ok(certs)
+}
+
+fn root_store(path: &Path) -> Result<RootCertStore, TlsError> {
+    let mut roots = RootCertStore::empty();
+    for cert in read_certs(path)? {
+        roots.add(cert).map_err(|err| invalid_file(path, err))?;
+    }
+    Ok(roots)
+}
+
+fn invalid_file(path: &Path, err: impl ToString) -> TlsError {
+    TlsError::InvalidFile(path.to_owned(), err.to_string())
+}
+
+/// Accepts any server certificate, but still checks the handshake signatures.
+#[derive(Debug)]
+struct NoVerifier(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Verifies that the server certificate is signed by a trusted authority, whatever
+/// the host it's issued for.
+#[derive(Debug)]
+struct NoHostnameVerifier(Arc<WebPkiServerVerifier>);
+
+impl ServerCertVerifier for NoHostnameVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        // The chain is verified before the name
+        match self
+            .0
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+        {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+            )) => Ok(ServerCertVerified::assertion()),
+            res => res,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.0.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.0.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_verify_schemes()
+    }
+}
@@ -0,0 +1,165 @@
+use crate::client::config::{Host, KeepaliveOptions};
+use crate::client::error::{ConnectionError, TlsError};
+use crate::client::open_stream;
+use crate::client::tls::{
+    client_config, default_cert_path, negotiate, ChannelBinding, SslMode, TlsOptions,
+};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Self-signed certificate used as the root certificate.
+fn root_cert() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src/client/tls/root.crt")
+}
+
+fn options(mode: SslMode, root_cert: Option<PathBuf>) -> TlsOptions {
+    TlsOptions::new(mode, root_cert, None, None, ChannelBinding::default())
+}
+
+/// Accepts a connection and answers its `SSLRequest` with `answer`.
+async fn accept_ssl_request(listener: &TcpListener, answer: u8) -> TcpStream {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut request = [0; 8];
+    stream.read_exact(&mut request).await.unwrap();
+    assert_eq!(request, [0, 0, 0, 8, 4, 210, 22, 47]);
+    stream.write_all(&[answer]).await.unwrap();
+    stream
+}
+
+/// Stand-in server answering the `SSLRequest` with `answer`, which then returns the
+/// first byte sent by the client after the answer.
+async fn stand_in_server(answer: u8) -> (TcpStream, JoinHandle<u8>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let mut stream = accept_ssl_request(&listener, answer).await;
+        stream.read_u8().await.unwrap()
+    });
+    (TcpStream::connect(addr).await.unwrap(), server)
+}
+
+#[test]
+fn test_parse_ssl_mode() {
+    assert_eq!("disable".parse::<SslMode>(), Ok(SslMode::Disable));
+    assert_eq!("prefer".parse::<SslMode>(), Ok(SslMode::Prefer));
+    assert_eq!("require".parse::<SslMode>(), Ok(SslMode::Require));
+    assert_eq!("verify-ca".parse::<SslMode>(), Ok(SslMode::VerifyCa));
+    assert_eq!("verify-full".parse::<SslMode>(), Ok(SslMode::VerifyFull));
+    assert_eq!(
+        "allow".parse::<SslMode>(),
+        Err("Unsupported sslmode: allow".to_owned())
+    );
+}
+
+#[test]
+fn test_parse_channel_binding() {
+    assert_eq!(
+        "disable".parse::<ChannelBinding>(),
+        Ok(ChannelBinding::Disable)
+    );
+    assert_eq!(
+        "prefer".parse::<ChannelBinding>(),
+        Ok(ChannelBinding::Prefer)
+    );
+    assert_eq!(
+        "require".parse::<ChannelBinding>(),
+        Ok(ChannelBinding::Require)
+    );
+    assert!("allow".parse::<ChannelBinding>().is_err());
+}
+
+#[tokio::test]
+async fn test_negotiate_prefer_fallback() {
+    let (stream, server) = stand_in_server(b'N').await;
+    let (mut stream, server_cert) = negotiate(stream, "localhost", &options(SslMode::Prefer, None))
+        .await
+        .unwrap();
+    assert!(server_cert.is_none());
+    // The connection goes on in plaintext
+    stream.write_all(b"Q").await.unwrap();
+    assert_eq!(server.await.unwrap(), b'Q');
+}
+
+#[tokio::test]
+async fn test_negotiate_require_not_supported() {
+    let (stream, _server) = stand_in_server(b'N').await;
+    let res = negotiate(stream, "localhost", &options(SslMode::Require, None)).await;
+    assert!(matches!(
+        res,
+        Err(ConnectionError::TlsError(TlsError::NotSupported))
+    ));
+}
+
+#[tokio::test]
+async fn test_negotiate_handshake() {
+    let (stream, server) = stand_in_server(b'S').await;
+    let res = negotiate(stream, "localhost", &options(SslMode::Prefer, None)).await;
+    // The stand-in server closes the connection after the `ClientHello` record
+    assert_eq!(server.await.unwrap(), 0x16);
+    // Even when TLS is only preferred, as the stream is consumed by the handshake and
+    // falling back requires a new connection
+    assert!(matches!(
+        res,
+        Err(ConnectionError::TlsError(TlsError::HandshakeError(_)))
+    ));
+}
+
+#[tokio::test]
+async fn test_open_stream_handshake_fallback() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let host = Host {
+        host: "127.0.0.1".to_owned(),
+        port: listener.local_addr().unwrap().port(),
+        password: None,
+    };
+    let server = tokio::spawn(async move {
+        // Closes the connection after the `ClientHello` record
+        let mut stream = accept_ssl_request(&listener, b'S').await;
+        stream.read_u8().await.unwrap();
+        drop(stream);
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream.read_u8().await.unwrap()
+    });
+    let keepalives = KeepaliveOptions::default();
+
+    let (mut stream, server_cert) =
+        open_stream(&host, &options(SslMode::Prefer, None), &keepalives)
+            .await
+            .unwrap();
+    assert!(server_cert.is_none());
+    // The connection goes on in plaintext over a new connection
+    stream.write_all(b"Q").await.unwrap();
+    assert_eq!(server.await.unwrap(), b'Q');
+}
+
+#[test]
+fn test_client_config() {
+    let modes = [
+        SslMode::Prefer,
+        SslMode::Require,
+        SslMode::VerifyCa,
+        SslMode::VerifyFull,
+    ];
+    for mode in modes {
+        assert!(client_config(&options(mode, Some(root_cert()))).is_ok());
+        assert!(matches!(
+            client_config(&options(mode, Some(PathBuf::from("missing.crt")))),
+            Err(TlsError::InvalidFile(_, _))
+        ));
+    }
+
+    // Without a root certificate, which also defaults to `~/.postgresql/root.crt`
+    if default_cert_path("root.crt").is_some_and(|path| path.is_file()) {
+        return;
+    }
+    assert!(client_config(&options(SslMode::Prefer, None)).is_ok());
+    assert!(client_config(&options(SslMode::Require, None)).is_ok());
+    for mode in [SslMode::VerifyCa, SslMode::VerifyFull] {
+        assert!(matches!(
+            client_config(&options(mode, None)),
+            Err(TlsError::InvalidFile(_, _))
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{Connection, FetchError};
+use crate::messages::TransactionStatus;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl FromStr for IsolationLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read_uncommitted" => Ok(IsolationLevel::ReadUncommitted),
+            "read_committed" => Ok(IsolationLevel::ReadCommitted),
+            "repeatable_read" => Ok(IsolationLevel::RepeatableRead),
+            "serializable" => Ok(IsolationLevel::Serializable),
+            _ => Err(format!("Unsupported isolation level: {}", s)),
+        }
+    }
+}
+
+impl IsolationLevel {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TransactionOptions {
+    /// Isolation level, the server default when `None`.
+    pub isolation_level: Option<IsolationLevel>,
+    pub read_only: bool,
+    /// Only has an effect on read-only serializable transactions, which then wait to
+    /// take a snapshot that can't fail with a serialization error.
+    pub deferrable: bool,
+}
+
+impl TransactionOptions {
+    pub fn new(isolation_level: Option<IsolationLevel>, read_only: bool, deferrable: bool) -> Self {
+        TransactionOptions {
+            isolation_level,
+            read_only,
+            deferrable,
+        }
+    }
+
+    fn begin_query(&self) -> String {
+        let mut modes = vec![];
+        if let Some(isolation_level) = self.isolation_level {
+            modes.push(format!("ISOLATION LEVEL {}", isolation_level.as_sql()));
+        }
+        if self.read_only {
+            modes.push("READ ONLY".to_owned());
+        }
+        if self.deferrable {
+            modes.push("DEFERRABLE".to_owned());
+        }
+        if modes.is_empty() {
+            "BEGIN".to_owned()
+        } else {
+            format!("BEGIN {}", modes.join(", "))
+        }
+    }
+}
+
+/// Transaction, or savepoint when nested, that's finished with `commit` or `rollback`.
+///
+/// Queries are run through the transaction, which dereferences to the connection. A
+/// transaction dropped without being finished is rolled back right before the next
+/// message is sent to the server.
+pub struct Transaction<'a> {
+    connection: &'a mut Connection,
+    /// `None` for the outermost transaction.
+    savepoint: Option<String>,
+    done: bool,
+}
+
+impl<'a> Transaction<'a> {
+    /// Commits the transaction, or releases the savepoint.
+    pub async fn commit(mut self) -> Result<(), FetchError> {
+        match self.savepoint.to_owned() {
+            Some(name) => self.connection.release_savepoint(&name).await?,
+            None => self.connection.commit().await?,
+        }
+        self.done = true;
+        Ok(())
+    }
+
+    /// Rolls back the transaction, or rolls back to the savepoint and releases it.
+    pub async fn rollback(mut self) -> Result<(), FetchError> {
+        match self.savepoint.to_owned() {
+            Some(name) => self.connection.rollback_to_savepoint(&name).await?,
+            None => self.connection.rollback().await?,
+        }
+        self.done = true;
+        Ok(())
+    }
+
+    /// Starts a nested transaction backed by a savepoint.
+    pub async fn savepoint(&mut self) -> Result<Transaction<'_>, FetchError> {
+        let name = self.connection.savepoint().await?;
+        Ok(Transaction {
+            connection: self.connection,
+            savepoint: Some(name),
+            done: false,
+        })
+    }
+}
+
+impl<'a> Deref for Transaction<'a> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.connection
+    }
+}
+
+impl<'a> DerefMut for Transaction<'a> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.connection
+    }
+}
+
+impl<'a> Drop for Transaction<'a> {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        let rollback = match &self.savepoint {
+            Some(name) => rollback_to_savepoint_query(name),
+            None => "ROLLBACK".to_owned(),
+        };
+        self.connection.pending_rollbacks.push(rollback);
+    }
+}
+
+impl Connection {
+    /// Starts a transaction with the server default options.
+    pub async fn transaction(&mut self) -> Result<Transaction<'_>, FetchError> {
+        self.transaction_with_options(TransactionOptions::default())
+            .await
+    }
+
+    pub async fn transaction_with_options(
+        &mut self,
+        options: TransactionOptions,
+    ) -> Result<Transaction<'_>, FetchError> {
+        self.begin(&options).await?;
+        Ok(Transaction {
+            connection: self,
+            savepoint: None,
+            done: false,
+        })
+    }
+
+    /// Starts a transaction, which must then be finished with `commit` or `rollback`.
+    /// Prefer `transaction`, which finishes it when dropped.
+    pub async fn begin(&mut self, options: &TransactionOptions) -> Result<(), FetchError> {
+        // The status is stale until the pending rollbacks are done
+        self.write_pending_rollbacks().await?;
+        self.skip_unread_responses().await?;
+        if self.transaction_status != TransactionStatus::Idle {
+            return Err(FetchError::InvalidStateError(
+                "a transaction is already in progress".to_owned(),
+            ));
+        }
+        self.run_command(options.begin_query()).await
+    }
+
+    /// Commits the current transaction. A failed transaction is rolled back instead,
+    /// and an error is returned.
+    pub async fn commit(&mut self) -> Result<(), FetchError> {
+        if self.transaction_status == TransactionStatus::Failed {
+            self.rollback().await?;
+            return Err(FetchError::InvalidStateError(
+                "the transaction failed and was rolled back".to_owned(),
+            ));
+        }
+        self.run_command("COMMIT".to_owned()).await
+    }
+
+    pub async fn rollback(&mut self) -> Result<(), FetchError> {
+        self.run_command("ROLLBACK".to_owned()).await
+    }
+
+    /// Creates a savepoint in the current transaction, and returns its name.
+    pub async fn savepoint(&mut self) -> Result<String, FetchError> {
+        self.next_savepoint_id += 1;
+        let name = format!("ohmyfpg_savepoint_{}", self.next_savepoint_id);
+        self.run_command(format!("SAVEPOINT {}", name)).await?;
+        Ok(name)
+    }
+
+    pub async fn release_savepoint(&mut self, name: &str) -> Result<(), FetchError> {
+        self.run_command(format!("RELEASE SAVEPOINT {}", name))
+            .await
+    }
+
+    /// Rolls back to the savepoint, and releases it.
+    pub async fn rollback_to_savepoint(&mut self, name: &str) -> Result<(), FetchError> {
+        self.run_command(rollback_to_savepoint_query(name)).await
+    }
+}
+
+fn rollback_to_savepoint_query(name: &str) -> String {
+    format!(
+        "ROLLBACK TO SAVEPOINT {name}; RELEASE SAVEPOINT {name}",
+        name = name
+    )
+}
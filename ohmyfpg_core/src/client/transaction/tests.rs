@@ -0,0 +1,154 @@
+use crate::client::mock_server::{
+    command_complete, connection, error_response, ready_for_query, sent_messages,
+};
+use crate::client::{FetchError, IsolationLevel, TransactionOptions};
+use crate::messages::TransactionStatus;
+use tokio::io::{AsyncWriteExt, DuplexStream};
+
+/// Returns the text of the simple queries sent by the connection so far.
+async fn sent_queries(server: &mut DuplexStream) -> Vec<String> {
+    sent_messages(server)
+        .await
+        .into_iter()
+        .filter(|(msg_type, _)| *msg_type == b'Q')
+        .map(|(_, body)| String::from_utf8_lossy(&body[..body.len() - 1]).into_owned())
+        .collect()
+}
+
+/// Responses to a simple query running the given commands.
+fn command_responses(tags: &[&str], status: u8) -> Vec<u8> {
+    let mut responses = vec![];
+    for tag in tags {
+        responses.extend(command_complete(tag));
+    }
+    responses.extend(ready_for_query(status));
+    responses
+}
+
+#[test]
+fn test_begin_query() {
+    assert_eq!(TransactionOptions::default().begin_query(), "BEGIN");
+    assert_eq!(
+        TransactionOptions::new(Some(IsolationLevel::Serializable), true, true).begin_query(),
+        "BEGIN ISOLATION LEVEL SERIALIZABLE, READ ONLY, DEFERRABLE"
+    );
+    assert_eq!(
+        TransactionOptions::new(Some(IsolationLevel::RepeatableRead), false, false).begin_query(),
+        "BEGIN ISOLATION LEVEL REPEATABLE READ"
+    );
+}
+
+#[tokio::test]
+async fn test_commit() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        command_responses(&["BEGIN"], b'T'),
+        command_responses(&["INSERT 0 1"], b'T'),
+        command_responses(&["COMMIT"], b'I'),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    let mut tx = conn.transaction().await.unwrap();
+    assert_eq!(tx.transaction_status(), TransactionStatus::InTransaction);
+    tx.simple_query("INSERT INTO t VALUES (1)".to_owned())
+        .await
+        .unwrap();
+    // Another transaction can't be started meanwhile
+    assert!(matches!(
+        tx.begin(&TransactionOptions::default()).await,
+        Err(FetchError::InvalidStateError(_))
+    ));
+    tx.commit().await.unwrap();
+
+    assert_eq!(conn.transaction_status(), TransactionStatus::Idle);
+    assert_eq!(
+        sent_queries(&mut server).await,
+        ["BEGIN", "INSERT INTO t VALUES (1)", "COMMIT"]
+    );
+}
+
+#[tokio::test]
+async fn test_commit_failed() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        command_responses(&["BEGIN"], b'T'),
+        error_response("22012", "division by zero"),
+        ready_for_query(b'E'),
+        command_responses(&["ROLLBACK"], b'I'),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    let mut tx = conn.transaction().await.unwrap();
+    assert!(tx.simple_query("SELECT 1 / 0".to_owned()).await.is_err());
+    // Rolled back instead
+    assert!(matches!(
+        tx.commit().await,
+        Err(FetchError::InvalidStateError(_))
+    ));
+    assert_eq!(conn.transaction_status(), TransactionStatus::Idle);
+    assert_eq!(
+        sent_queries(&mut server).await,
+        ["BEGIN", "SELECT 1 / 0", "ROLLBACK"]
+    );
+}
+
+#[tokio::test]
+async fn test_rollback_on_drop() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        command_responses(&["BEGIN"], b'T'),
+        command_responses(&["ROLLBACK"], b'I'),
+        command_responses(&["SELECT 0"], b'I'),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    let tx = conn.transaction().await.unwrap();
+    drop(tx);
+    // Sent along with the next query
+    assert_eq!(conn.pending_rollbacks, ["ROLLBACK"]);
+    let results = conn.simple_query("SELECT".to_owned()).await.unwrap();
+    // The response of the rollback is skipped
+    assert_eq!(results[0].tag.as_ref().unwrap().command, "SELECT");
+
+    assert!(conn.pending_rollbacks.is_empty());
+    assert_eq!(conn.transaction_status(), TransactionStatus::Idle);
+    assert_eq!(
+        sent_queries(&mut server).await,
+        ["BEGIN", "ROLLBACK", "SELECT"]
+    );
+}
+
+#[tokio::test]
+async fn test_savepoints() {
+    let (mut conn, mut server) = connection();
+    let responses = [
+        command_responses(&["BEGIN"], b'T'),
+        command_responses(&["SAVEPOINT"], b'T'),
+        command_responses(&["SAVEPOINT"], b'T'),
+        command_responses(&["RELEASE"], b'T'),
+        command_responses(&["ROLLBACK", "RELEASE"], b'T'),
+        command_responses(&["COMMIT"], b'I'),
+    ];
+    server.write_all(&responses.concat()).await.unwrap();
+
+    let mut tx = conn.transaction().await.unwrap();
+    let mut outer = tx.savepoint().await.unwrap();
+    let inner = outer.savepoint().await.unwrap();
+    inner.commit().await.unwrap();
+    // Rolled back to, right before the commit of the transaction
+    drop(outer);
+    tx.commit().await.unwrap();
+
+    assert_eq!(conn.transaction_status(), TransactionStatus::Idle);
+    assert_eq!(
+        sent_queries(&mut server).await,
+        [
+            "BEGIN",
+            "SAVEPOINT ohmyfpg_savepoint_1",
+            "SAVEPOINT ohmyfpg_savepoint_2",
+            "RELEASE SAVEPOINT ohmyfpg_savepoint_2",
+            "ROLLBACK TO SAVEPOINT ohmyfpg_savepoint_1; RELEASE SAVEPOINT ohmyfpg_savepoint_1",
+            "COMMIT",
+        ]
+    );
+}
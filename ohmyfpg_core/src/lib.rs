@@ -1,3 +1,4 @@
 pub mod client;
 pub mod messages;
 pub mod server;
+pub mod types;
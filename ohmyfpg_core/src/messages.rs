@@ -1,12 +1,18 @@
 pub mod authentication;
+pub mod copy;
 pub mod error;
 pub use error::UnrecognizedMessageError;
 pub mod query;
 pub mod startup;
 use authentication::{
-    AuthenticationOk, AuthenticationSASL, AuthenticationSASLContinue, AuthenticationSASLFinal,
+    AuthenticationCleartextPassword, AuthenticationMD5Password, AuthenticationOk,
+    AuthenticationSASL, AuthenticationSASLContinue, AuthenticationSASLFinal,
+};
+use copy::{CopyData, CopyDone, CopyInResponse, CopyOutResponse};
+use query::{
+    BindComplete, CloseComplete, CommandComplete, DataRow, EmptyQueryResponse, NoData,
+    ParameterDescription, ParseComplete, PortalSuspended, RowDescription,
 };
-use query::{BindComplete, CommandComplete, DataRow, ParseComplete, RowDescription};
 use std;
 use std::collections::HashMap;
 
@@ -89,6 +95,10 @@ impl RawBackendMessage {
                     )),
                     12_u32 => Ok(RawTypedBackendMessage::AuthenticationSASLFinal(self.body)),
                     0_u32 => Ok(RawTypedBackendMessage::AuthenticationOk(self.body)),
+                    3_u32 => Ok(RawTypedBackendMessage::AuthenticationCleartextPassword(
+                        self.body,
+                    )),
+                    5_u32 => Ok(RawTypedBackendMessage::AuthenticationMD5Password(self.body)),
                     _ => Err(error::UnrecognizedMessageError::new(self)),
                 }
             }
@@ -110,6 +120,27 @@ impl RawBackendMessage {
             query::BIND_COMPLETE_MESSAGE_TYPE => {
                 Ok(RawTypedBackendMessage::BindComplete(self.body))
             }
+            query::CLOSE_COMPLETE_MESSAGE_TYPE => {
+                Ok(RawTypedBackendMessage::CloseComplete(self.body))
+            }
+            query::PORTAL_SUSPENDED_MESSAGE_TYPE => {
+                Ok(RawTypedBackendMessage::PortalSuspended(self.body))
+            }
+            query::PARAMETER_DESCRIPTION_MESSAGE_TYPE => {
+                Ok(RawTypedBackendMessage::ParameterDescription(self.body))
+            }
+            query::NO_DATA_MESSAGE_TYPE => Ok(RawTypedBackendMessage::NoData(self.body)),
+            query::EMPTY_QUERY_RESPONSE_MESSAGE_TYPE => {
+                Ok(RawTypedBackendMessage::EmptyQueryResponse(self.body))
+            }
+            copy::COPY_IN_RESPONSE_MESSAGE_TYPE => {
+                Ok(RawTypedBackendMessage::CopyInResponse(self.body))
+            }
+            copy::COPY_OUT_RESPONSE_MESSAGE_TYPE => {
+                Ok(RawTypedBackendMessage::CopyOutResponse(self.body))
+            }
+            copy::COPY_DATA_MESSAGE_TYPE => Ok(RawTypedBackendMessage::CopyData(self.body)),
+            copy::COPY_DONE_MESSAGE_TYPE => Ok(RawTypedBackendMessage::CopyDone(self.body)),
             _ => Err(error::UnrecognizedMessageError::new(self)),
         }
     }
@@ -125,6 +156,8 @@ pub enum RawTypedBackendMessage {
     AuthenticationSASLContinue(Vec<u8>),
     AuthenticationSASLFinal(Vec<u8>),
     AuthenticationOk(Vec<u8>),
+    AuthenticationCleartextPassword(Vec<u8>),
+    AuthenticationMD5Password(Vec<u8>),
     ErrorResponse(Vec<u8>),
     ParameterStatus(Vec<u8>),
     BackendKeyData(Vec<u8>),
@@ -135,6 +168,15 @@ pub enum RawTypedBackendMessage {
     CommandComplete(Vec<u8>),
     ParseComplete(Vec<u8>),
     BindComplete(Vec<u8>),
+    CloseComplete(Vec<u8>),
+    PortalSuspended(Vec<u8>),
+    ParameterDescription(Vec<u8>),
+    NoData(Vec<u8>),
+    EmptyQueryResponse(Vec<u8>),
+    CopyInResponse(Vec<u8>),
+    CopyOutResponse(Vec<u8>),
+    CopyData(Vec<u8>),
+    CopyDone(Vec<u8>),
 }
 
 impl RawTypedBackendMessage {
@@ -156,21 +198,31 @@ impl RawTypedBackendMessage {
             RawTypedBackendMessage::AuthenticationOk(body) => {
                 BackendMessage::AuthenticationOk(AuthenticationOk::deserialize_body(body))
             }
+            RawTypedBackendMessage::AuthenticationCleartextPassword(body) => {
+                BackendMessage::AuthenticationCleartextPassword(
+                    AuthenticationCleartextPassword::deserialize_body(body),
+                )
+            }
+            RawTypedBackendMessage::AuthenticationMD5Password(body) => {
+                BackendMessage::AuthenticationMD5Password(
+                    AuthenticationMD5Password::deserialize_body(body),
+                )
+            }
 
             RawTypedBackendMessage::ErrorResponse(body) => {
                 BackendMessage::ErrorResponse(ErrorResponse::deserialize_body(body))
             }
-            RawTypedBackendMessage::ParameterStatus(_) => {
-                BackendMessage::ParameterStatus(ParameterStatus::default())
+            RawTypedBackendMessage::ParameterStatus(body) => {
+                BackendMessage::ParameterStatus(ParameterStatus::deserialize_body(body))
             }
-            RawTypedBackendMessage::BackendKeyData(_) => {
-                BackendMessage::BackendKeyData(BackendKeyData::default())
+            RawTypedBackendMessage::BackendKeyData(body) => {
+                BackendMessage::BackendKeyData(BackendKeyData::deserialize_body(body))
             }
             RawTypedBackendMessage::NoticeResponse(_) => {
                 BackendMessage::NoticeResponse(NoticeResponse::default())
             }
-            RawTypedBackendMessage::ReadyForQuery(_) => {
-                BackendMessage::ReadyForQuery(ReadyForQuery::default())
+            RawTypedBackendMessage::ReadyForQuery(body) => {
+                BackendMessage::ReadyForQuery(ReadyForQuery::deserialize_body(body))
             }
             RawTypedBackendMessage::RowDescription(body) => {
                 BackendMessage::RowDescription(RowDescription::deserialize_body(body))
@@ -187,6 +239,33 @@ impl RawTypedBackendMessage {
             RawTypedBackendMessage::BindComplete(body) => {
                 BackendMessage::BindComplete(BindComplete::deserialize_body(body))
             }
+            RawTypedBackendMessage::CloseComplete(body) => {
+                BackendMessage::CloseComplete(CloseComplete::deserialize_body(body))
+            }
+            RawTypedBackendMessage::PortalSuspended(body) => {
+                BackendMessage::PortalSuspended(PortalSuspended::deserialize_body(body))
+            }
+            RawTypedBackendMessage::ParameterDescription(body) => {
+                BackendMessage::ParameterDescription(ParameterDescription::deserialize_body(body))
+            }
+            RawTypedBackendMessage::NoData(body) => {
+                BackendMessage::NoData(NoData::deserialize_body(body))
+            }
+            RawTypedBackendMessage::EmptyQueryResponse(body) => {
+                BackendMessage::EmptyQueryResponse(EmptyQueryResponse::deserialize_body(body))
+            }
+            RawTypedBackendMessage::CopyInResponse(body) => {
+                BackendMessage::CopyInResponse(CopyInResponse::deserialize_body(body))
+            }
+            RawTypedBackendMessage::CopyOutResponse(body) => {
+                BackendMessage::CopyOutResponse(CopyOutResponse::deserialize_body(body))
+            }
+            RawTypedBackendMessage::CopyData(body) => {
+                BackendMessage::CopyData(CopyData::deserialize_body(body))
+            }
+            RawTypedBackendMessage::CopyDone(body) => {
+                BackendMessage::CopyDone(CopyDone::deserialize_body(body))
+            }
         }
     }
 }
@@ -197,6 +276,8 @@ pub enum BackendMessage {
     AuthenticationSASLContinue(AuthenticationSASLContinue),
     AuthenticationSASLFinal(AuthenticationSASLFinal),
     AuthenticationOk(AuthenticationOk),
+    AuthenticationCleartextPassword(AuthenticationCleartextPassword),
+    AuthenticationMD5Password(AuthenticationMD5Password),
     ErrorResponse(ErrorResponse),
     ParameterStatus(ParameterStatus),
     BackendKeyData(BackendKeyData),
@@ -207,6 +288,15 @@ pub enum BackendMessage {
     CommandComplete(CommandComplete),
     ParseComplete(ParseComplete),
     BindComplete(BindComplete),
+    CloseComplete(CloseComplete),
+    PortalSuspended(PortalSuspended),
+    ParameterDescription(ParameterDescription),
+    NoData(NoData),
+    EmptyQueryResponse(EmptyQueryResponse),
+    CopyInResponse(CopyInResponse),
+    CopyOutResponse(CopyOutResponse),
+    CopyData(CopyData),
+    CopyDone(CopyDone),
 }
 
 #[derive(Debug)]
@@ -226,17 +316,95 @@ impl ErrorResponse {
     }
 }
 
+/// Run-time parameter reported by the server, e.g. at startup.
 #[derive(Debug, Default)]
-pub struct ParameterStatus {}
+pub struct ParameterStatus {
+    pub name: String,
+    pub value: String,
+}
 
-#[derive(Debug, Default)]
-pub struct BackendKeyData {}
+impl ParameterStatus {
+    pub fn new(name: String, value: String) -> Self {
+        ParameterStatus { name, value }
+    }
+}
+
+impl DeserializeMessage for ParameterStatus {
+    fn deserialize_body(body: Vec<u8>) -> Self {
+        let mut fields = body
+            .split(|b| *b == 0)
+            .map(|field| String::from_utf8_lossy(field).into_owned());
+        let name = fields.next().unwrap_or_default();
+        let value = fields.next().unwrap_or_default();
+        ParameterStatus::new(name, value)
+    }
+}
+
+/// Identifies the backend process of the connection, so that its queries can be
+/// cancelled with a `CancelRequest`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BackendKeyData {
+    pub process_id: u32,
+    pub secret_key: u32,
+}
+
+impl BackendKeyData {
+    pub fn new(process_id: u32, secret_key: u32) -> Self {
+        BackendKeyData {
+            process_id,
+            secret_key,
+        }
+    }
+}
+
+impl DeserializeMessage for BackendKeyData {
+    fn deserialize_body(body: Vec<u8>) -> Self {
+        let process_id = u32::from_be_bytes(body[0..4].try_into().unwrap());
+        let secret_key = u32::from_be_bytes(body[4..8].try_into().unwrap());
+        BackendKeyData::new(process_id, secret_key)
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct NoticeResponse {}
 
+/// Transaction status of the backend, as reported by `ReadyForQuery`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransactionStatus {
+    /// Not in a transaction block.
+    #[default]
+    Idle,
+    InTransaction,
+    /// In a transaction block whose queries are rejected until it's rolled back.
+    Failed,
+}
+
+impl TransactionStatus {
+    pub fn from_byte(b: u8) -> Self {
+        match b {
+            b'T' => TransactionStatus::InTransaction,
+            b'E' => TransactionStatus::Failed,
+            _ => TransactionStatus::Idle,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
-pub struct ReadyForQuery {}
+pub struct ReadyForQuery {
+    pub status: TransactionStatus,
+}
+
+impl ReadyForQuery {
+    pub fn new(status: TransactionStatus) -> Self {
+        ReadyForQuery { status }
+    }
+}
+
+impl DeserializeMessage for ReadyForQuery {
+    fn deserialize_body(body: Vec<u8>) -> Self {
+        ReadyForQuery::new(TransactionStatus::from_byte(body[0]))
+    }
+}
 
 impl DeserializeMessage for ErrorResponse {
     fn deserialize_body(body: Vec<u8>) -> Self {
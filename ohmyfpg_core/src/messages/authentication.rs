@@ -1,7 +1,13 @@
 #[cfg(test)]
 mod tests;
-use crate::messages::DeserializeMessage;
+use crate::client::{ConnectionError, FetchError};
+use crate::messages::{BackendMessage, DeserializeMessage};
+mod password;
 mod sasl;
+pub use password::authenticate as password_authenticate;
+pub use password::{
+    md5_password, AuthenticationCleartextPassword, AuthenticationMD5Password, PasswordMessage,
+};
 pub use sasl::authenticate as sasl_authenticate;
 pub use sasl::{
     AuthenticationSASL, AuthenticationSASLContinue, AuthenticationSASLFinal, SASLInitialResponse,
@@ -30,3 +36,12 @@ impl DeserializeMessage for AuthenticationOk {
         AuthenticationOk::new()
     }
 }
+
+/// Converts the message received in place of the expected one during the
+/// authentication into an error, e.g. the server rejecting the password.
+pub fn unexpected_message_error(msg: BackendMessage) -> ConnectionError {
+    match msg {
+        BackendMessage::ErrorResponse(err) => ConnectionError::from(err),
+        msg => ConnectionError::from(FetchError::UnexpectedMessageError(msg)),
+    }
+}
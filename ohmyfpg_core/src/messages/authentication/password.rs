@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests;
+use crate::client::{Connection, ConnectionError};
+use crate::messages::authentication::unexpected_message_error;
+#[cfg(test)]
+use crate::messages::authentication::AUTH_MESSAGE_TYPE;
+use crate::messages::{
+    BackendMessage, DeserializeMessage, SerializeMessage, SerializeMessageBytes,
+};
+
+const PASSWORD_MESSAGE_TYPE: &[u8; 1] = b"p";
+
+#[derive(Debug, Default)]
+pub struct AuthenticationCleartextPassword {}
+
+impl DeserializeMessage for AuthenticationCleartextPassword {
+    fn deserialize_body(_: Vec<u8>) -> Self {
+        AuthenticationCleartextPassword::default()
+    }
+}
+
+#[derive(Debug)]
+pub struct AuthenticationMD5Password {
+    pub salt: [u8; 4],
+}
+
+impl AuthenticationMD5Password {
+    pub fn new(salt: [u8; 4]) -> Self {
+        AuthenticationMD5Password { salt }
+    }
+}
+
+#[cfg(test)]
+impl SerializeMessage for AuthenticationMD5Password {
+    fn get_msg_type(&self) -> Option<&[u8; 1]> {
+        Some(AUTH_MESSAGE_TYPE)
+    }
+
+    fn serialize_body(self) -> Vec<u8> {
+        let mut body = 5_u32.to_msg_bytes();
+        body.extend_from_slice(&self.salt);
+        body
+    }
+}
+
+impl DeserializeMessage for AuthenticationMD5Password {
+    fn deserialize_body(body: Vec<u8>) -> Self {
+        AuthenticationMD5Password::new(body[4..8].try_into().unwrap())
+    }
+}
+
+#[derive(Debug)]
+pub struct PasswordMessage {
+    pub password: String,
+}
+
+impl PasswordMessage {
+    pub fn new(password: String) -> Self {
+        PasswordMessage { password }
+    }
+}
+
+impl SerializeMessage for PasswordMessage {
+    fn get_msg_type(&self) -> Option<&[u8; 1]> {
+        Some(PASSWORD_MESSAGE_TYPE)
+    }
+
+    fn serialize_body(self) -> Vec<u8> {
+        self.password.to_msg_bytes()
+    }
+}
+
+/// Sends the password, either in clear text or already hashed, and waits for the
+/// server to accept it.
+pub async fn authenticate(
+    connection: &mut Connection,
+    password: String,
+) -> Result<(), ConnectionError> {
+    connection
+        .write_message(PasswordMessage::new(password))
+        .await?;
+    match connection.read_message().await? {
+        BackendMessage::AuthenticationOk(_) => Ok(()),
+        msg => Err(unexpected_message_error(msg)),
+    }
+}
+
+/// Returns the password hashed as expected by the MD5 authentication, i.e.
+/// `md5` followed by the hex digest of the salted digest of the password and user.
+pub fn md5_password(user: &str, password: &str, salt: [u8; 4]) -> String {
+    let digest = md5::compute(format!("{}{}", password, user));
+    let mut salted = format!("{:x}", digest).into_bytes();
+    salted.extend_from_slice(&salt);
+    format!("md5{:x}", md5::compute(salted))
+}
@@ -0,0 +1,28 @@
+use crate::messages::authentication::password::{
+    md5_password, AuthenticationMD5Password, PasswordMessage,
+};
+use crate::messages::{DeserializeMessage, SerializeMessage};
+
+#[test]
+fn test_serialize_deserialize() {
+    let m1 = AuthenticationMD5Password::new([1, 2, 3, 4]);
+    let bytes = vec![82, 0, 0, 0, 12, 0, 0, 0, 5, 1, 2, 3, 4];
+    assert_eq!(m1.serialize(), bytes);
+
+    let m2 = AuthenticationMD5Password::deserialize_body(bytes[5..].to_vec());
+    assert_eq!(m2.salt, [1, 2, 3, 4]);
+
+    let m3 = PasswordMessage::new("pencil".to_owned());
+    assert_eq!(
+        m3.serialize(),
+        vec![112, 0, 0, 0, 11, 112, 101, 110, 99, 105, 108, 0]
+    );
+}
+
+#[test]
+fn test_md5_password() {
+    assert_eq!(
+        md5_password("user", "pencil", [1, 2, 3, 4]),
+        "md54376eb6913b38f9aaff38dc7cf19ca76"
+    );
+}
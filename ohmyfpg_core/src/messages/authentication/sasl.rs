@@ -1,12 +1,20 @@
 mod frontend;
+#[cfg(test)]
+mod tests;
 pub use frontend::{SASLInitialResponse, SASLResponse};
 mod backend;
-use crate::client::{Connection, ConnectionError};
+mod scram;
+use crate::client::{ChannelBinding, Connection, ConnectionError, SASLAuthError};
+use crate::messages::authentication::unexpected_message_error;
 use crate::messages::BackendMessage;
 pub use backend::{AuthenticationSASL, AuthenticationSASLContinue, AuthenticationSASLFinal};
-use scram::ScramClient;
+use ring::digest;
+use scram::{Gs2ChannelBinding, ScramClient};
 
 const SASL_FE_MESSAGE_TYPE: &[u8; 1] = b"p";
+const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+const SCRAM_SHA_256_PLUS: &str = "SCRAM-SHA-256-PLUS";
+const DER_OID_TAG: u8 = 0x06;
 
 // References:
 // - https://www.postgresql.org/docs/current/sasl-authentication.html
@@ -18,44 +26,142 @@ const SASL_FE_MESSAGE_TYPE: &[u8; 1] = b"p";
 //   - RFC 4422
 //   - RFC 5802
 //   - RFC 5803
+//   - RFC 5929
 //   - RFC 7677
 
+/// Authenticates with SCRAM-SHA-256, or SCRAM-SHA-256-PLUS when the connection uses
+/// TLS, in which case `server_cert` is the server certificate.
 pub async fn authenticate(
     connection: &mut Connection,
     user: &str,
     password: &str,
     auth_sasl: AuthenticationSASL,
-    // TODO: Replace `ConnectionError` with a proper `SASLAuthError` to be thrown instead
-    // of `unwrap`-ing
+    channel_binding: ChannelBinding,
+    server_cert: Option<&[u8]>,
 ) -> Result<(), ConnectionError> {
-    // TODO: Only SCRAM-SHA-256 is supported, add check here
-    let mechanism = auth_sasl.mechanisms[0].to_owned();
-    println!("Starting SASL/{} auth...", mechanism);
-    let scram = ScramClient::new(user, password, None);
-    let (scram, client_first) = scram.client_first();
-    let sasl_init_resp = SASLInitialResponse::new(mechanism, client_first);
+    let (mechanism, gs2_channel_binding) =
+        select_mechanism(&auth_sasl.mechanisms, channel_binding, server_cert)?;
+    let scram = ScramClient::new(user, password, gs2_channel_binding);
+    let sasl_init_resp = SASLInitialResponse::new(mechanism.to_owned(), scram.client_first());
     connection.write_message(sasl_init_resp).await?;
 
+    let server_first = match connection.read_message().await? {
+        BackendMessage::AuthenticationSASLContinue(sasl_cont) => sasl_cont.server_first,
+        msg => return Err(unexpected_message_error(msg)),
+    };
+    let (client_final, server_signature) = scram.handle_server_first(&server_first)?;
+    let sasl_resp = SASLResponse::new(client_final);
+    connection.write_message(sasl_resp).await?;
+
+    // The server rejects a wrong password with an `ErrorResponse` at this point
+    let server_final = match connection.read_message().await? {
+        BackendMessage::AuthenticationSASLFinal(sasl_final) => sasl_final.server_final,
+        msg => return Err(unexpected_message_error(msg)),
+    };
+    server_signature.verify(&server_final)?;
     match connection.read_message().await? {
-        BackendMessage::AuthenticationSASLContinue(sasl_cont) => {
-            let scram = scram.handle_server_first(&sasl_cont.server_first).unwrap();
-            let (scram, client_final) = scram.client_final();
-            let sasl_resp = SASLResponse::new(client_final);
-            connection.write_message(sasl_resp).await?;
-            match connection.read_message().await? {
-                BackendMessage::AuthenticationSASLFinal(sasl_final) => {
-                    scram.handle_server_final(&sasl_final.server_final).unwrap();
-                    match connection.read_message().await? {
-                        BackendMessage::AuthenticationOk(_) => {
-                            println!("Auth successfull!");
-                            Ok(())
-                        }
-                        _ => todo!("Error"),
-                    }
-                }
-                _ => todo!("Error"),
-            }
+        BackendMessage::AuthenticationOk(_) => Ok(()),
+        msg => Err(unexpected_message_error(msg)),
+    }
+}
+
+/// Selects the mechanism among the ones offered by the server, along with the
+/// channel binding to use. Channel binding is only used over TLS, and when required
+/// it's an error to fall back to a mechanism without it.
+fn select_mechanism(
+    mechanisms: &[String],
+    channel_binding: ChannelBinding,
+    server_cert: Option<&[u8]>,
+) -> Result<(&'static str, Gs2ChannelBinding), ConnectionError> {
+    let plus_offered = mechanisms.iter().any(|m| m == SCRAM_SHA_256_PLUS);
+    let end_point = match channel_binding {
+        ChannelBinding::Disable => None,
+        _ => server_cert.and_then(tls_server_end_point),
+    };
+    match (channel_binding, plus_offered, end_point) {
+        (_, true, Some(end_point)) => Ok((
+            SCRAM_SHA_256_PLUS,
+            Gs2ChannelBinding::TlsServerEndPoint(end_point),
+        )),
+        (ChannelBinding::Require, _, _) => {
+            let reason = match (server_cert, plus_offered) {
+                (None, _) => "the connection does not use TLS",
+                (Some(_), false) => "the server does not support it",
+                (Some(_), true) => "the server certificate signature algorithm is unsupported",
+            };
+            Err(ConnectionError::ChannelBindingError(format!(
+                "channel binding is required, but {}",
+                reason
+            )))
         }
-        _ => todo!("Error"),
+        _ if !mechanisms.iter().any(|m| m == SCRAM_SHA_256) => Err(ConnectionError::from(
+            SASLAuthError::UnsupportedMechanisms(mechanisms.to_vec()),
+        )),
+        (ChannelBinding::Disable, _, _) => Ok((SCRAM_SHA_256, Gs2ChannelBinding::Unsupported)),
+        // Lets the server detect that the offer of SCRAM-SHA-256-PLUS was stripped
+        (_, false, _) if server_cert.is_some() => {
+            Ok((SCRAM_SHA_256, Gs2ChannelBinding::NotOffered))
+        }
+        _ => Ok((SCRAM_SHA_256, Gs2ChannelBinding::Unsupported)),
     }
 }
+
+/// Returns the `tls-server-end-point` channel binding data of the server
+/// certificate, i.e. its hash with the hash function of its signature algorithm,
+/// or SHA-256 when it's MD5 or SHA-1.
+///
+/// Reference: https://www.rfc-editor.org/rfc/rfc5929#section-4.1
+fn tls_server_end_point(cert: &[u8]) -> Option<Vec<u8>> {
+    let algorithm = match signature_algorithm(cert)? {
+        // md5WithRSAEncryption, sha1WithRSAEncryption, sha256WithRSAEncryption
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 4 | 5 | 11] => &digest::SHA256,
+        // sha384WithRSAEncryption
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 12] => &digest::SHA384,
+        // sha512WithRSAEncryption
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 13] => &digest::SHA512,
+        // ecdsa-with-SHA1
+        [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x01] => &digest::SHA256,
+        // ecdsa-with-SHA256, ecdsa-with-SHA384, ecdsa-with-SHA512
+        [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 2] => &digest::SHA256,
+        [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 3] => &digest::SHA384,
+        [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 4] => &digest::SHA512,
+        // Algorithms without a single hash function, e.g. Ed25519 and RSASSA-PSS
+        _ => return None,
+    };
+    Some(digest::digest(algorithm, cert).as_ref().to_vec())
+}
+
+/// Returns the DER-encoded OID of the signature algorithm of the certificate.
+fn signature_algorithm(cert: &[u8]) -> Option<&[u8]> {
+    // Certificate ::= SEQUENCE { tbsCertificate, signatureAlgorithm, signature }
+    let (_, cert, _) = der_element(cert)?;
+    let (_, _, rest) = der_element(cert)?;
+    // AlgorithmIdentifier ::= SEQUENCE { algorithm OBJECT IDENTIFIER, parameters }
+    let (_, algorithm, _) = der_element(rest)?;
+    match der_element(algorithm)? {
+        (DER_OID_TAG, oid, _) => Some(oid),
+        _ => None,
+    }
+}
+
+/// Returns the tag and the content of the DER element at the start of `data`, and
+/// the data after it.
+fn der_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, data) = data.split_first()?;
+    let (&len, mut data) = data.split_first()?;
+    let len = if len < 0x80 {
+        len as usize
+    } else {
+        // Long form, the length is encoded in the next bytes
+        let len_size = (len & 0x7f) as usize;
+        if len_size > 4 {
+            return None;
+        }
+        let len_bytes = data.get(..len_size)?;
+        data = &data[len_size..];
+        len_bytes
+            .iter()
+            .fold(0, |len, byte| (len << 8) | *byte as usize)
+    };
+    Some((tag, data.get(..len)?, &data[len..]))
+}
@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod tests;
+use crate::client::SASLAuthError;
+use ring::{digest, hmac, pbkdf2};
+use std::num::NonZeroU32;
+
+const CLIENT_NONCE_SIZE: usize = 18;
+
+/// Channel binding flag of the GS2 header sent in the client-first message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Gs2ChannelBinding {
+    /// The client doesn't support channel binding.
+    Unsupported,
+    /// The client supports channel binding, but thinks the server doesn't.
+    NotOffered,
+    /// `tls-server-end-point` binding to the given hash of the server certificate.
+    TlsServerEndPoint(Vec<u8>),
+}
+
+impl Gs2ChannelBinding {
+    fn header(&self) -> &'static str {
+        match self {
+            Gs2ChannelBinding::Unsupported => "n,,",
+            Gs2ChannelBinding::NotOffered => "y,,",
+            Gs2ChannelBinding::TlsServerEndPoint(_) => "p=tls-server-end-point,,",
+        }
+    }
+
+    /// Value of the `c` attribute of the client-final message.
+    fn encode(&self) -> String {
+        let mut data = self.header().as_bytes().to_vec();
+        if let Gs2ChannelBinding::TlsServerEndPoint(hash) = self {
+            data.extend_from_slice(hash);
+        }
+        base64::encode(data)
+    }
+}
+
+/// Client side of a SCRAM-SHA-256 exchange, optionally with channel binding.
+///
+/// Unlike the `scram` crate, the GS2 header isn't hard-wired, so that channel
+/// binding can be negotiated.
+pub struct ScramClient {
+    channel_binding: Gs2ChannelBinding,
+    password: String,
+    client_nonce: String,
+    client_first_bare: String,
+}
+
+impl ScramClient {
+    pub fn new(user: &str, password: &str, channel_binding: Gs2ChannelBinding) -> Self {
+        let mut nonce = [0u8; CLIENT_NONCE_SIZE];
+        getrandom::getrandom(&mut nonce).unwrap();
+        Self::new_with_nonce(user, password, channel_binding, base64::encode(nonce))
+    }
+
+    fn new_with_nonce(
+        user: &str,
+        password: &str,
+        channel_binding: Gs2ChannelBinding,
+        client_nonce: String,
+    ) -> Self {
+        // Servers ignore the user in favor of the one of the startup message
+        let user = user.replace('=', "=3D").replace(',', "=2C");
+        let client_first_bare = format!("n={},r={}", user, client_nonce);
+        ScramClient {
+            channel_binding,
+            password: password.to_owned(),
+            client_nonce,
+            client_first_bare,
+        }
+    }
+
+    pub fn client_first(&self) -> String {
+        format!(
+            "{}{}",
+            self.channel_binding.header(),
+            self.client_first_bare
+        )
+    }
+
+    /// Returns the client-final message, and the signature the server must answer
+    /// with.
+    pub fn handle_server_first(
+        self,
+        server_first: &str,
+    ) -> Result<(String, ServerSignature), SASLAuthError> {
+        let invalid = |msg: &str| SASLAuthError::InvalidServerFirst(msg.to_owned());
+        let mut attributes = server_first.split(',');
+        let nonce = attributes
+            .next()
+            .and_then(|attr| attr.strip_prefix("r="))
+            .ok_or_else(|| invalid("missing nonce"))?;
+        let salt = attributes
+            .next()
+            .and_then(|attr| attr.strip_prefix("s="))
+            .and_then(|salt| base64::decode(salt).ok())
+            .ok_or_else(|| invalid("missing or invalid salt"))?;
+        let iterations = attributes
+            .next()
+            .and_then(|attr| attr.strip_prefix("i="))
+            .and_then(|iterations| iterations.parse::<NonZeroU32>().ok())
+            .ok_or_else(|| invalid("missing or invalid iteration count"))?;
+        if !nonce.starts_with(&self.client_nonce) || nonce.len() == self.client_nonce.len() {
+            return Err(invalid("nonce does not extend the client nonce"));
+        }
+
+        let mut salted_password = [0u8; digest::SHA256_OUTPUT_LEN];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            &salt,
+            self.password.as_bytes(),
+            &mut salted_password,
+        );
+        let salted_password = hmac::Key::new(hmac::HMAC_SHA256, &salted_password);
+        let client_key = hmac::sign(&salted_password, b"Client Key");
+        let stored_key = digest::digest(&digest::SHA256, client_key.as_ref());
+        let server_key = hmac::sign(&salted_password, b"Server Key");
+
+        let client_final_without_proof = format!("c={},r={}", self.channel_binding.encode(), nonce);
+        let auth_message = format!(
+            "{},{},{}",
+            self.client_first_bare, server_first, client_final_without_proof
+        );
+        let client_signature = hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, stored_key.as_ref()),
+            auth_message.as_bytes(),
+        );
+        let client_proof: Vec<u8> = client_key
+            .as_ref()
+            .iter()
+            .zip(client_signature.as_ref())
+            .map(|(key, signature)| key ^ signature)
+            .collect();
+        let server_signature = hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, server_key.as_ref()),
+            auth_message.as_bytes(),
+        );
+
+        let client_final = format!(
+            "{},p={}",
+            client_final_without_proof,
+            base64::encode(client_proof)
+        );
+        Ok((
+            client_final,
+            ServerSignature(server_signature.as_ref().to_vec()),
+        ))
+    }
+}
+
+/// Signature proving that the server knows the password too.
+pub struct ServerSignature(Vec<u8>);
+
+impl ServerSignature {
+    pub fn verify(&self, server_final: &str) -> Result<(), SASLAuthError> {
+        if let Some(err) = server_final.strip_prefix("e=") {
+            return Err(SASLAuthError::Rejected(err.to_owned()));
+        }
+        let signature = server_final
+            .split(',')
+            .next()
+            .and_then(|attr| attr.strip_prefix("v="))
+            .and_then(|signature| base64::decode(signature).ok())
+            .ok_or_else(|| SASLAuthError::InvalidServerFinal("missing verifier".to_owned()))?;
+        if signature != self.0 {
+            return Err(SASLAuthError::ServerSignatureMismatch);
+        }
+        Ok(())
+    }
+}
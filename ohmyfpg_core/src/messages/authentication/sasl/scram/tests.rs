@@ -0,0 +1,66 @@
+use crate::client::SASLAuthError;
+use crate::messages::authentication::sasl::scram::{Gs2ChannelBinding, ScramClient};
+
+// Reference: https://www.rfc-editor.org/rfc/rfc7677#section-3
+#[test]
+fn test_scram_sha_256() {
+    let client = ScramClient::new_with_nonce(
+        "user",
+        "pencil",
+        Gs2ChannelBinding::Unsupported,
+        "rOprNGfwEbeRWgbNEkqO".to_owned(),
+    );
+    assert_eq!(client.client_first(), "n,,n=user,r=rOprNGfwEbeRWgbNEkqO");
+
+    let server_first =
+        "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096";
+    let (client_final, server_signature) = client.handle_server_first(server_first).unwrap();
+    assert_eq!(
+        client_final,
+        "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ="
+    );
+    server_signature
+        .verify("v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=")
+        .unwrap();
+    assert!(matches!(
+        server_signature.verify("v=AAAATRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4="),
+        Err(SASLAuthError::ServerSignatureMismatch)
+    ));
+    assert!(matches!(
+        server_signature.verify("e=invalid-proof"),
+        Err(SASLAuthError::Rejected(err)) if err == "invalid-proof"
+    ));
+}
+
+#[test]
+fn test_channel_binding() {
+    let client = ScramClient::new_with_nonce(
+        "user",
+        "pencil",
+        Gs2ChannelBinding::TlsServerEndPoint(vec![1, 2, 3]),
+        "nonce".to_owned(),
+    );
+    assert_eq!(
+        client.client_first(),
+        "p=tls-server-end-point,,n=user,r=nonce"
+    );
+    let (client_final, _) = client
+        .handle_server_first("r=nonce+server,s=c2FsdA==,i=1")
+        .unwrap();
+    assert!(client_final.starts_with(&format!(
+        "c={},r=nonce+server,p=",
+        base64::encode(b"p=tls-server-end-point,,\x01\x02\x03")
+    )));
+
+    let client = ScramClient::new_with_nonce(
+        "user",
+        "pencil",
+        Gs2ChannelBinding::NotOffered,
+        "nonce".to_owned(),
+    );
+    assert_eq!(client.client_first(), "y,,n=user,r=nonce");
+    assert!(matches!(
+        client.handle_server_first("r=other,s=c2FsdA==,i=1"),
+        Err(SASLAuthError::InvalidServerFirst(_))
+    ));
+}
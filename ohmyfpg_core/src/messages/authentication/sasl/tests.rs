@@ -0,0 +1,78 @@
+use crate::client::{ChannelBinding, ConnectionError, SASLAuthError};
+use crate::messages::authentication::sasl::scram::Gs2ChannelBinding;
+use crate::messages::authentication::sasl::{select_mechanism, tls_server_end_point};
+
+// Certificate whose signature algorithm is sha256WithRSAEncryption, with an empty
+// `tbsCertificate` and signature
+const CERT: &[u8] = &[
+    0x30, 0x13, 0x30, 0x00, 0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01,
+    0x0b, 0x05, 0x00, 0x03, 0x00,
+];
+
+#[test]
+fn test_select_mechanism() {
+    let plain = vec!["SCRAM-SHA-256".to_owned()];
+    let plus = vec!["SCRAM-SHA-256-PLUS".to_owned(), "SCRAM-SHA-256".to_owned()];
+
+    let (mechanism, binding) = select_mechanism(&plus, ChannelBinding::Prefer, Some(CERT)).unwrap();
+    assert_eq!(mechanism, "SCRAM-SHA-256-PLUS");
+    assert!(matches!(binding, Gs2ChannelBinding::TlsServerEndPoint(hash) if hash.len() == 32));
+
+    let (mechanism, binding) =
+        select_mechanism(&plus, ChannelBinding::Disable, Some(CERT)).unwrap();
+    assert_eq!(mechanism, "SCRAM-SHA-256");
+    assert_eq!(binding, Gs2ChannelBinding::Unsupported);
+
+    let (mechanism, binding) =
+        select_mechanism(&plain, ChannelBinding::Prefer, Some(CERT)).unwrap();
+    assert_eq!(mechanism, "SCRAM-SHA-256");
+    assert_eq!(binding, Gs2ChannelBinding::NotOffered);
+
+    let (mechanism, binding) = select_mechanism(&plain, ChannelBinding::Prefer, None).unwrap();
+    assert_eq!(mechanism, "SCRAM-SHA-256");
+    assert_eq!(binding, Gs2ChannelBinding::Unsupported);
+
+    let (mechanism, _) = select_mechanism(&plus, ChannelBinding::Require, Some(CERT)).unwrap();
+    assert_eq!(mechanism, "SCRAM-SHA-256-PLUS");
+    assert!(matches!(
+        select_mechanism(&plain, ChannelBinding::Require, Some(CERT)),
+        Err(ConnectionError::ChannelBindingError(_))
+    ));
+    assert!(matches!(
+        select_mechanism(&plus, ChannelBinding::Require, None),
+        Err(ConnectionError::ChannelBindingError(_))
+    ));
+
+    let unsupported = vec!["SCRAM-SHA-1".to_owned()];
+    assert!(matches!(
+        select_mechanism(&unsupported, ChannelBinding::Disable, None),
+        Err(ConnectionError::SASLAuthError(SASLAuthError::UnsupportedMechanisms(mechanisms)))
+            if mechanisms == unsupported
+    ));
+}
+
+#[test]
+fn test_tls_server_end_point() {
+    // Certificate with an empty `tbsCertificate` and signature, whose signature
+    // algorithm is `oid` followed by `NULL` parameters
+    let cert = |oid: &[u8]| {
+        let mut algorithm = vec![0x06, oid.len() as u8];
+        algorithm.extend_from_slice(oid);
+        algorithm.extend_from_slice(&[0x05, 0x00]);
+        let mut content = vec![0x30, 0x00, 0x30, algorithm.len() as u8];
+        content.extend_from_slice(&algorithm);
+        content.extend_from_slice(&[0x03, 0x00]);
+        let mut cert = vec![0x30, content.len() as u8];
+        cert.extend_from_slice(&content);
+        cert
+    };
+    let sha1_rsa = cert(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 5]);
+    assert_eq!(tls_server_end_point(&sha1_rsa).unwrap().len(), 32);
+    let sha384_rsa = cert(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 12]);
+    assert_eq!(tls_server_end_point(&sha384_rsa).unwrap().len(), 48);
+    let sha512_ecdsa = cert(&[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 4]);
+    assert_eq!(tls_server_end_point(&sha512_ecdsa).unwrap().len(), 64);
+    let ed25519 = cert(&[0x2b, 0x65, 0x70]);
+    assert_eq!(tls_server_end_point(&ed25519), None);
+    assert_eq!(tls_server_end_point(&sha1_rsa[..10]), None);
+}
@@ -0,0 +1,9 @@
+mod frontend;
+#[cfg(test)]
+mod tests;
+pub use frontend::{CopyData, CopyDone, CopyFail};
+mod backend;
+pub use backend::{
+    CopyInResponse, CopyOutResponse, COPY_DATA_MESSAGE_TYPE, COPY_DONE_MESSAGE_TYPE,
+    COPY_IN_RESPONSE_MESSAGE_TYPE, COPY_OUT_RESPONSE_MESSAGE_TYPE,
+};
@@ -0,0 +1,71 @@
+use crate::messages::copy::{CopyData, CopyDone};
+use crate::messages::DeserializeMessage;
+
+pub const COPY_IN_RESPONSE_MESSAGE_TYPE: &[u8; 1] = b"G";
+pub const COPY_OUT_RESPONSE_MESSAGE_TYPE: &[u8; 1] = b"H";
+// Sent by both the frontend and the backend
+pub const COPY_DATA_MESSAGE_TYPE: &[u8; 1] = b"d";
+pub const COPY_DONE_MESSAGE_TYPE: &[u8; 1] = b"c";
+
+/// Sent when the server is ready to receive the data of a `COPY FROM STDIN`.
+#[derive(Debug)]
+pub struct CopyInResponse {
+    /// `0` for text and `1` for binary.
+    pub format: u8,
+    pub column_formats: Vec<u16>,
+}
+
+impl CopyInResponse {
+    pub fn new(format: u8, column_formats: Vec<u16>) -> Self {
+        CopyInResponse {
+            format,
+            column_formats,
+        }
+    }
+}
+
+impl DeserializeMessage for CopyInResponse {
+    fn deserialize_body(body: Vec<u8>) -> Self {
+        let column_formats = body[3..]
+            .chunks_exact(2)
+            .map(|chunk| u16::from_be_bytes(chunk.try_into().unwrap()))
+            .collect();
+        CopyInResponse::new(body[0], column_formats)
+    }
+}
+
+/// Sent when the server starts sending the data of a `COPY TO STDOUT`.
+#[derive(Debug)]
+pub struct CopyOutResponse {
+    /// `0` for text and `1` for binary.
+    pub format: u8,
+    pub column_formats: Vec<u16>,
+}
+
+impl CopyOutResponse {
+    pub fn new(format: u8, column_formats: Vec<u16>) -> Self {
+        CopyOutResponse {
+            format,
+            column_formats,
+        }
+    }
+}
+
+impl DeserializeMessage for CopyOutResponse {
+    fn deserialize_body(body: Vec<u8>) -> Self {
+        let copy_in_response = CopyInResponse::deserialize_body(body);
+        CopyOutResponse::new(copy_in_response.format, copy_in_response.column_formats)
+    }
+}
+
+impl DeserializeMessage for CopyData {
+    fn deserialize_body(body: Vec<u8>) -> Self {
+        CopyData::new(body)
+    }
+}
+
+impl DeserializeMessage for CopyDone {
+    fn deserialize_body(_body: Vec<u8>) -> Self {
+        CopyDone::default()
+    }
+}
@@ -0,0 +1,81 @@
+use crate::messages::copy::{COPY_DATA_MESSAGE_TYPE, COPY_DONE_MESSAGE_TYPE};
+use crate::messages::{SerializeMessage, SerializeMessageBytes};
+
+const COPY_FAIL_MESSAGE_TYPE: &[u8; 1] = b"f";
+
+/// Chunk of the data stream of a `COPY`, which doesn't need to be aligned with rows.
+pub struct CopyData {
+    pub data: Vec<u8>,
+}
+
+impl CopyData {
+    pub fn new(data: Vec<u8>) -> Self {
+        CopyData { data }
+    }
+}
+
+// The data is left out as it can be arbitrarily large
+impl std::fmt::Debug for CopyData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyData")
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+impl SerializeMessage for CopyData {
+    fn get_msg_type(&self) -> Option<&[u8; 1]> {
+        Some(COPY_DATA_MESSAGE_TYPE)
+    }
+
+    fn serialize_body(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[derive(Debug)]
+pub struct CopyDone {}
+
+impl CopyDone {
+    fn new() -> Self {
+        CopyDone {}
+    }
+}
+
+impl Default for CopyDone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerializeMessage for CopyDone {
+    fn get_msg_type(&self) -> Option<&[u8; 1]> {
+        Some(COPY_DONE_MESSAGE_TYPE)
+    }
+
+    fn serialize_body(self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+/// Aborts a `COPY FROM STDIN`, the server replies with an error having `message`.
+#[derive(Debug)]
+pub struct CopyFail {
+    pub message: String,
+}
+
+impl CopyFail {
+    pub fn new(message: String) -> Self {
+        CopyFail { message }
+    }
+}
+
+impl SerializeMessage for CopyFail {
+    fn get_msg_type(&self) -> Option<&[u8; 1]> {
+        Some(COPY_FAIL_MESSAGE_TYPE)
+    }
+
+    fn serialize_body(self) -> Vec<u8> {
+        self.message.to_msg_bytes()
+    }
+}
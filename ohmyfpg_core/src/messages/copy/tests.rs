@@ -0,0 +1,21 @@
+use crate::messages::copy::{CopyData, CopyDone, CopyFail, CopyInResponse};
+use crate::messages::{DeserializeMessage, SerializeMessage};
+
+#[test]
+fn test_serialize_copy() {
+    let m1 = CopyData::new(vec![1, 2]);
+    assert_eq!(m1.serialize(), vec![100, 0, 0, 0, 6, 1, 2]);
+
+    let m2 = CopyDone::default();
+    assert_eq!(m2.serialize(), vec![99, 0, 0, 0, 4]);
+
+    let m3 = CopyFail::new("no".to_owned());
+    assert_eq!(m3.serialize(), vec![102, 0, 0, 0, 7, 110, 111, 0]);
+}
+
+#[test]
+fn test_deserialize_copy_in_response() {
+    let m = CopyInResponse::deserialize_body(vec![1, 0, 2, 0, 1, 0, 1]);
+    assert_eq!(m.format, 1);
+    assert_eq!(m.column_formats, vec![1, 1]);
+}
@@ -1,8 +1,13 @@
 mod frontend;
-pub use frontend::{Bind, Describe, Execute, Flush, Format, Parse, Query, Sync};
+#[cfg(test)]
+mod tests;
+pub use frontend::{Bind, Close, Describe, Execute, Flush, Format, Parse, Query, Sync, Target};
 mod backend;
 pub use backend::{
-    BindComplete, CommandComplete, DataRow, ParseComplete, RowDescription,
-    BIND_COMPLETE_MESSAGE_TYPE, COMMAND_COMPLETE_MESSAGE_TYPE, DATA_ROW_MESSAGE_TYPE,
-    PARSE_COMPLETE_MESSAGE_TYPE, ROW_DESCRIPTION_MESSAGE_TYPE,
+    BindComplete, CloseComplete, CommandComplete, DataRow, EmptyQueryResponse, NoData,
+    ParameterDescription, ParseComplete, PortalSuspended, RowDescription,
+    BIND_COMPLETE_MESSAGE_TYPE, CLOSE_COMPLETE_MESSAGE_TYPE, COMMAND_COMPLETE_MESSAGE_TYPE,
+    DATA_ROW_MESSAGE_TYPE, EMPTY_QUERY_RESPONSE_MESSAGE_TYPE, NO_DATA_MESSAGE_TYPE,
+    PARAMETER_DESCRIPTION_MESSAGE_TYPE, PARSE_COMPLETE_MESSAGE_TYPE, PORTAL_SUSPENDED_MESSAGE_TYPE,
+    ROW_DESCRIPTION_MESSAGE_TYPE,
 };
@@ -6,8 +6,13 @@ pub const DATA_ROW_MESSAGE_TYPE: &[u8; 1] = b"D";
 pub const COMMAND_COMPLETE_MESSAGE_TYPE: &[u8; 1] = b"C";
 pub const PARSE_COMPLETE_MESSAGE_TYPE: &[u8; 1] = b"1";
 pub const BIND_COMPLETE_MESSAGE_TYPE: &[u8; 1] = b"2";
+pub const CLOSE_COMPLETE_MESSAGE_TYPE: &[u8; 1] = b"3";
+pub const PORTAL_SUSPENDED_MESSAGE_TYPE: &[u8; 1] = b"s";
+pub const PARAMETER_DESCRIPTION_MESSAGE_TYPE: &[u8; 1] = b"t";
+pub const NO_DATA_MESSAGE_TYPE: &[u8; 1] = b"n";
+pub const EMPTY_QUERY_RESPONSE_MESSAGE_TYPE: &[u8; 1] = b"I";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FieldDescription {
     pub name: String,
     pub data_type_oid: u32,
@@ -22,7 +27,7 @@ impl FieldDescription {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RowDescription {
     pub fields: Vec<FieldDescription>,
 }
@@ -160,3 +165,110 @@ impl DeserializeMessage for BindComplete {
         BindComplete::new()
     }
 }
+
+#[derive(Debug)]
+pub struct CloseComplete {}
+
+impl CloseComplete {
+    fn new() -> Self {
+        CloseComplete {}
+    }
+}
+
+impl Default for CloseComplete {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeserializeMessage for CloseComplete {
+    fn deserialize_body(_body: Vec<u8>) -> Self {
+        CloseComplete::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct PortalSuspended {}
+
+impl PortalSuspended {
+    fn new() -> Self {
+        PortalSuspended {}
+    }
+}
+
+impl Default for PortalSuspended {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeserializeMessage for PortalSuspended {
+    fn deserialize_body(_body: Vec<u8>) -> Self {
+        PortalSuspended::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParameterDescription {
+    pub param_types: Vec<u32>,
+}
+
+impl ParameterDescription {
+    pub fn new(param_types: Vec<u32>) -> Self {
+        ParameterDescription { param_types }
+    }
+}
+
+impl DeserializeMessage for ParameterDescription {
+    fn deserialize_body(body: Vec<u8>) -> Self {
+        let param_types = body[2..]
+            .chunks_exact(4)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()))
+            .collect();
+        ParameterDescription::new(param_types)
+    }
+}
+
+/// Sent instead of a `RowDescription` for statements that don't return rows.
+#[derive(Debug)]
+pub struct NoData {}
+
+impl NoData {
+    fn new() -> Self {
+        NoData {}
+    }
+}
+
+impl Default for NoData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeserializeMessage for NoData {
+    fn deserialize_body(_body: Vec<u8>) -> Self {
+        NoData::new()
+    }
+}
+
+/// Sent instead of a `CommandComplete` for an empty query string.
+#[derive(Debug)]
+pub struct EmptyQueryResponse {}
+
+impl EmptyQueryResponse {
+    fn new() -> Self {
+        EmptyQueryResponse {}
+    }
+}
+
+impl Default for EmptyQueryResponse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeserializeMessage for EmptyQueryResponse {
+    fn deserialize_body(_body: Vec<u8>) -> Self {
+        EmptyQueryResponse::new()
+    }
+}
@@ -7,6 +7,27 @@ const BIND_MESSAGE_TYPE: &[u8; 1] = b"B";
 const DESCRIBE_MESSAGE_TYPE: &[u8; 1] = b"D";
 const EXECUTE_MESSAGE_TYPE: &[u8; 1] = b"E";
 const SYNC_MESSAGE_TYPE: &[u8; 1] = b"S";
+const CLOSE_MESSAGE_TYPE: &[u8; 1] = b"C";
+
+/// Prepared statement or portal targeted by a `Describe` or a `Close`. Empty names
+/// refer to the unnamed ones.
+#[derive(Debug)]
+pub enum Target {
+    Statement(String),
+    Portal(String),
+}
+
+impl Target {
+    fn serialize(self) -> Vec<u8> {
+        let (target_type, mut name) = match self {
+            Target::Statement(name) => (b'S', name.to_msg_bytes()),
+            Target::Portal(name) => (b'P', name.to_msg_bytes()),
+        };
+        let mut body = vec![target_type];
+        body.append(&mut name);
+        body
+    }
+}
 
 #[derive(Debug)]
 pub struct Query {
@@ -31,12 +52,32 @@ impl SerializeMessage for Query {
 
 #[derive(Debug)]
 pub struct Parse {
+    /// Name of the prepared statement, the empty string selects the unnamed one.
+    pub statement_name: String,
     pub query_string: String,
+    /// OIDs of the parameters types, `0` lets the server infer the type.
+    pub param_types: Vec<u32>,
 }
 
 impl Parse {
     pub fn new(query_string: String) -> Self {
-        Parse { query_string }
+        Self::new_with_param_types(query_string, vec![])
+    }
+
+    pub fn new_with_param_types(query_string: String, param_types: Vec<u32>) -> Self {
+        Self::new_with_statement("".to_owned(), query_string, param_types)
+    }
+
+    pub fn new_with_statement(
+        statement_name: String,
+        query_string: String,
+        param_types: Vec<u32>,
+    ) -> Self {
+        Parse {
+            statement_name,
+            query_string,
+            param_types,
+        }
     }
 }
 
@@ -47,10 +88,14 @@ impl SerializeMessage for Parse {
 
     fn serialize_body(self) -> Vec<u8> {
         let mut body = vec![];
-        let mut prepared_stmt_name = "".to_string().to_msg_bytes();
+        let mut prepared_stmt_name = self.statement_name.to_msg_bytes();
         body.append(&mut prepared_stmt_name);
         body.append(&mut self.query_string.to_msg_bytes());
-        body.append(&mut 0u16.to_msg_bytes());
+        let param_types_count: u16 = self.param_types.len().try_into().unwrap();
+        body.append(&mut param_types_count.to_msg_bytes());
+        for param_type in self.param_types.into_iter() {
+            body.append(&mut param_type.to_msg_bytes());
+        }
         body
     }
 }
@@ -88,12 +133,44 @@ pub enum Format {
 
 #[derive(Debug)]
 pub struct Bind {
+    pub portal_name: String,
+    /// Name of the prepared statement, the empty string selects the unnamed one.
+    pub statement_name: String,
+    /// Format of the result columns.
     pub format: Format,
+    /// Parameters values in binary format, `None` stands for `NULL`.
+    pub params: Vec<Option<Vec<u8>>>,
 }
 
 impl Bind {
     pub fn new(format: Format) -> Self {
-        Bind { format }
+        Self::new_with_portal("".to_owned(), format)
+    }
+
+    pub fn new_with_portal(portal_name: String, format: Format) -> Self {
+        Self::new_with_params(portal_name, format, vec![])
+    }
+
+    pub fn new_with_params(
+        portal_name: String,
+        format: Format,
+        params: Vec<Option<Vec<u8>>>,
+    ) -> Self {
+        Self::new_with_statement(portal_name, "".to_owned(), format, params)
+    }
+
+    pub fn new_with_statement(
+        portal_name: String,
+        statement_name: String,
+        format: Format,
+        params: Vec<Option<Vec<u8>>>,
+    ) -> Self {
+        Bind {
+            portal_name,
+            statement_name,
+            format,
+            params,
+        }
     }
 }
 
@@ -104,17 +181,29 @@ impl SerializeMessage for Bind {
 
     fn serialize_body(self) -> Vec<u8> {
         let mut body = vec![];
-        let mut portal_name = "".to_string().to_msg_bytes();
-        let mut prepared_stmt_name = "".to_string().to_msg_bytes();
+        let mut portal_name = self.portal_name.to_msg_bytes();
+        let mut prepared_stmt_name = self.statement_name.to_msg_bytes();
         let format = match self.format {
             Format::Text => 0u16,
             Format::Binary => 1u16,
         };
         body.append(&mut portal_name);
         body.append(&mut prepared_stmt_name);
+        // Parameters are always sent in binary format
         body.append(&mut 1u16.to_msg_bytes());
-        body.append(&mut format.to_msg_bytes());
-        body.append(&mut 0u16.to_msg_bytes());
+        body.append(&mut 1u16.to_msg_bytes());
+        let params_count: u16 = self.params.len().try_into().unwrap();
+        body.append(&mut params_count.to_msg_bytes());
+        for param in self.params.into_iter() {
+            match param {
+                Some(mut value) => {
+                    let value_len: u32 = value.len().try_into().unwrap();
+                    body.append(&mut value_len.to_msg_bytes());
+                    body.append(&mut value);
+                }
+                None => body.append(&mut (-1i32 as u32).to_msg_bytes()),
+            }
+        }
         body.append(&mut 1u16.to_msg_bytes());
         body.append(&mut format.to_msg_bytes());
 
@@ -123,17 +212,19 @@ impl SerializeMessage for Bind {
 }
 
 #[derive(Debug)]
-pub struct Describe {}
+pub struct Describe {
+    pub target: Target,
+}
 
 impl Describe {
-    fn new() -> Self {
-        Describe {}
+    pub fn new(target: Target) -> Self {
+        Describe { target }
     }
 }
 
 impl Default for Describe {
     fn default() -> Self {
-        Self::new()
+        Self::new(Target::Portal("".to_owned()))
     }
 }
 
@@ -143,27 +234,30 @@ impl SerializeMessage for Describe {
     }
 
     fn serialize_body(self) -> Vec<u8> {
-        let mut body = vec![];
-        let description_type = "P";
-        let mut portal_name = "".to_string().to_msg_bytes();
-        body.append(&mut description_type.as_bytes().to_vec());
-        body.append(&mut portal_name);
-        body
+        self.target.serialize()
     }
 }
 
 #[derive(Debug)]
-pub struct Execute {}
+pub struct Execute {
+    pub portal_name: String,
+    /// Maximum number of rows to return, `0` means no limit. When the limit is reached
+    /// the portal is suspended and the next rows can be fetched with another `Execute`.
+    pub max_rows: u32,
+}
 
 impl Execute {
-    fn new() -> Self {
-        Execute {}
+    pub fn new(portal_name: String, max_rows: u32) -> Self {
+        Execute {
+            portal_name,
+            max_rows,
+        }
     }
 }
 
 impl Default for Execute {
     fn default() -> Self {
-        Self::new()
+        Self::new("".to_owned(), 0)
     }
 }
 
@@ -174,9 +268,9 @@ impl SerializeMessage for Execute {
 
     fn serialize_body(self) -> Vec<u8> {
         let mut body = vec![];
-        let mut portal_name = "".to_string().to_msg_bytes();
+        let mut portal_name = self.portal_name.to_msg_bytes();
         body.append(&mut portal_name);
-        body.append(&mut 0u32.to_msg_bytes());
+        body.append(&mut self.max_rows.to_msg_bytes());
         body
     }
 }
@@ -205,3 +299,24 @@ impl SerializeMessage for Sync {
         vec![]
     }
 }
+
+#[derive(Debug)]
+pub struct Close {
+    pub target: Target,
+}
+
+impl Close {
+    pub fn new(target: Target) -> Self {
+        Close { target }
+    }
+}
+
+impl SerializeMessage for Close {
+    fn get_msg_type(&self) -> Option<&[u8; 1]> {
+        Some(CLOSE_MESSAGE_TYPE)
+    }
+
+    fn serialize_body(self) -> Vec<u8> {
+        self.target.serialize()
+    }
+}
@@ -0,0 +1,43 @@
+use crate::messages::query::{Bind, Close, Describe, Execute, Format, Parse, Target};
+use crate::messages::SerializeMessage;
+
+#[test]
+fn test_serialize_execute() {
+    let m1 = Execute::default();
+    assert_eq!(m1.serialize(), vec![69, 0, 0, 0, 9, 0, 0, 0, 0, 0]);
+
+    let m2 = Execute::new("p1".to_owned(), 100);
+    assert_eq!(
+        m2.serialize(),
+        vec![69, 0, 0, 0, 11, 112, 49, 0, 0, 0, 0, 100]
+    );
+}
+
+#[test]
+fn test_serialize_describe_close() {
+    let m1 = Describe::default();
+    assert_eq!(m1.serialize(), vec![68, 0, 0, 0, 6, 80, 0]);
+
+    let m2 = Close::new(Target::Portal("p1".to_owned()));
+    assert_eq!(m2.serialize(), vec![67, 0, 0, 0, 8, 80, 112, 49, 0]);
+
+    let m3 = Close::new(Target::Statement("s1".to_owned()));
+    assert_eq!(m3.serialize(), vec![67, 0, 0, 0, 8, 83, 115, 49, 0]);
+}
+
+#[test]
+fn test_serialize_parse_bind_params() {
+    let m1 = Parse::new_with_param_types("$1".to_owned(), vec![23]);
+    assert_eq!(
+        m1.serialize(),
+        vec![80, 0, 0, 0, 14, 0, 36, 49, 0, 0, 1, 0, 0, 0, 23]
+    );
+
+    let m2 = Bind::new_with_params("".to_owned(), Format::Binary, vec![Some(vec![7]), None]);
+    assert_eq!(
+        m2.serialize(),
+        vec![
+            66, 0, 0, 0, 25, 0, 0, 0, 1, 0, 1, 0, 2, 0, 0, 0, 1, 7, 255, 255, 255, 255, 0, 1, 0, 1
+        ]
+    );
+}
@@ -72,3 +72,49 @@ impl DeserializeMessage for StartupMessage {
         StartupMessage::new_with_version(version, params)
     }
 }
+
+/// Code sent in place of the protocol version to ask the server to use TLS.
+static SSL_REQUEST_CODE: u32 = 80877103;
+
+/// Asks the server to switch to TLS before the startup message. The server replies
+/// with a single byte, `S` if it accepts and `N` otherwise.
+#[derive(Debug, Default)]
+pub struct SslRequest {}
+
+impl SerializeMessage for SslRequest {
+    fn serialize_body(self) -> Vec<u8> {
+        SSL_REQUEST_CODE.to_msg_bytes()
+    }
+}
+
+/// Code sent in place of the protocol version to cancel a query.
+static CANCEL_REQUEST_CODE: u32 = 80877102;
+
+/// Asks the server to cancel the query being run by the backend identified by the
+/// `BackendKeyData`. It's sent over a new connection, which the server closes
+/// without replying.
+#[derive(Debug)]
+pub struct CancelRequest {
+    pub process_id: u32,
+    pub secret_key: u32,
+}
+
+impl CancelRequest {
+    pub fn new(process_id: u32, secret_key: u32) -> Self {
+        CancelRequest {
+            process_id,
+            secret_key,
+        }
+    }
+}
+
+impl SerializeMessage for CancelRequest {
+    fn serialize_body(self) -> Vec<u8> {
+        [
+            CANCEL_REQUEST_CODE.to_msg_bytes(),
+            self.process_id.to_msg_bytes(),
+            self.secret_key.to_msg_bytes(),
+        ]
+        .concat()
+    }
+}
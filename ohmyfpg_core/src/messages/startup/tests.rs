@@ -1,5 +1,5 @@
-use crate::messages::startup::StartupMessage;
-use crate::messages::{DeserializeMessage, SerializeMessage};
+use crate::messages::startup::{CancelRequest, SslRequest, StartupMessage};
+use crate::messages::{BackendKeyData, DeserializeMessage, SerializeMessage};
 
 #[test]
 fn test_serialize() {
@@ -17,3 +17,21 @@ fn test_serialize() {
     assert_eq!(m2.version, (3, 0));
     assert_eq!(m2.params, params);
 }
+
+#[test]
+fn test_serialize_ssl_request() {
+    let bytes = vec![0, 0, 0, 8, 4, 210, 22, 47];
+    assert_eq!(SslRequest::default().serialize(), bytes);
+}
+
+#[test]
+fn test_serialize_cancel_request() {
+    let bytes = vec![0, 0, 0, 16, 4, 210, 22, 46, 0, 0, 48, 57, 7, 91, 205, 21];
+    assert_eq!(CancelRequest::new(12345, 123456789).serialize(), bytes);
+}
+
+#[test]
+fn test_deserialize_backend_key_data() {
+    let key_data = BackendKeyData::deserialize_body(vec![0, 0, 48, 57, 7, 91, 205, 21]);
+    assert_eq!(key_data, BackendKeyData::new(12345, 123456789));
+}
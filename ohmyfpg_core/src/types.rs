@@ -0,0 +1,340 @@
+mod numeric;
+#[cfg(test)]
+mod tests;
+use crate::client::FetchError;
+use crate::server::PgType;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+pub use numeric::Numeric;
+use std::fmt;
+
+// Binary dates and timestamps are relative to 2000-01-01.
+// Reference: https://www.postgresql.org/docs/current/datatype-datetime.html
+fn pg_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+// OIDs of the built-in types that values are encoded as.
+// Reference: https://github.com/postgres/postgres/blob/REL_15_STABLE/src/include/catalog/pg_type.dat
+const UNSPECIFIED_OID: u32 = 0;
+const BOOL_OID: u32 = 16;
+const BYTEA_OID: u32 = 17;
+const NAME_OID: u32 = 19;
+const INT8_OID: u32 = 20;
+const INT2_OID: u32 = 21;
+const INT4_OID: u32 = 23;
+const TEXT_OID: u32 = 25;
+const FLOAT4_OID: u32 = 700;
+const FLOAT8_OID: u32 = 701;
+const NUMERIC_OID: u32 = 1700;
+const BPCHAR_OID: u32 = 1042;
+const VARCHAR_OID: u32 = 1043;
+const DATE_OID: u32 = 1082;
+const TIMESTAMP_OID: u32 = 1114;
+const TIMESTAMPTZ_OID: u32 = 1184;
+
+/// A single value decoded from, or encoded to, its binary representation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int2(i16),
+    Int4(i32),
+    Int8(i64),
+    Float4(f32),
+    Float8(f64),
+    Numeric(Numeric),
+    Text(String),
+    Bytea(Vec<u8>),
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    TimestampTz(NaiveDateTime),
+}
+
+impl Value {
+    /// Whether values of the given type can be decoded.
+    pub fn can_decode(pg_type: &PgType) -> bool {
+        matches!(
+            pg_type.name.as_str(),
+            "bool"
+                | "int2"
+                | "int4"
+                | "int8"
+                | "float4"
+                | "float8"
+                | "numeric"
+                | "text"
+                | "varchar"
+                | "bpchar"
+                | "name"
+                | "bytea"
+                | "date"
+                | "timestamp"
+                | "timestamptz"
+        )
+    }
+
+    /// Decodes the binary `raw` value of the given type. `None` stands for `NULL`.
+    ///
+    /// `FetchError::UnsupportedTypeError` is returned for the types that can't be
+    /// decoded, see `can_decode`.
+    pub fn decode(pg_type: &PgType, raw: Option<&[u8]>) -> Result<Value, FetchError> {
+        let unsupported =
+            || FetchError::UnsupportedTypeError(format!("type {} can't be decoded", pg_type.name));
+        let raw = match raw {
+            Some(raw) => raw,
+            None if Value::can_decode(pg_type) => return Ok(Value::Null),
+            None => return Err(unsupported()),
+        };
+        Ok(match pg_type.name.as_str() {
+            "bool" => Value::Bool(raw[0] != 0),
+            "int2" => Value::Int2(i16::from_be_bytes(raw.try_into().unwrap())),
+            "int4" => Value::Int4(i32::from_be_bytes(raw.try_into().unwrap())),
+            "int8" => Value::Int8(i64::from_be_bytes(raw.try_into().unwrap())),
+            "float4" => Value::Float4(f32::from_be_bytes(raw.try_into().unwrap())),
+            "float8" => Value::Float8(f64::from_be_bytes(raw.try_into().unwrap())),
+            "numeric" => Value::Numeric(Numeric::decode(raw).ok_or_else(|| {
+                FetchError::InvalidStateError("Malformed numeric value".to_owned())
+            })?),
+            "text" | "varchar" | "bpchar" | "name" => {
+                Value::Text(String::from_utf8(raw.to_vec()).unwrap())
+            }
+            "bytea" => Value::Bytea(raw.to_vec()),
+            "date" => {
+                let days = i32::from_be_bytes(raw.try_into().unwrap());
+                Value::Date(pg_epoch().date() + Duration::days(days.into()))
+            }
+            "timestamp" | "timestamptz" => {
+                let micros = i64::from_be_bytes(raw.try_into().unwrap());
+                let ts = pg_epoch() + Duration::microseconds(micros);
+                if pg_type.name == "timestamp" {
+                    Value::Timestamp(ts)
+                } else {
+                    Value::TimestampTz(ts)
+                }
+            }
+            _ => return Err(unsupported()),
+        })
+    }
+
+    /// Converts the value so that it can be bound to a parameter of type `type_oid`,
+    /// or returns `None` if it doesn't fit, e.g. an `Int8` out of the `int4` range.
+    pub fn cast(self, type_oid: u32) -> Option<Value> {
+        if is_binary_compatible(self.type_oid(), type_oid) {
+            return Some(self);
+        }
+        let int = match self {
+            Value::Int2(v) => Some(i64::from(v)),
+            Value::Int4(v) => Some(i64::from(v)),
+            Value::Int8(v) => Some(v),
+            _ => None,
+        };
+        match (self, int, type_oid) {
+            (_, Some(v), INT2_OID) => v.try_into().ok().map(Value::Int2),
+            (_, Some(v), INT4_OID) => v.try_into().ok().map(Value::Int4),
+            (_, Some(v), INT8_OID) => Some(Value::Int8(v)),
+            (_, Some(v), FLOAT4_OID) => Some(Value::Float4(v as f32)),
+            (_, Some(v), FLOAT8_OID) => Some(Value::Float8(v as f64)),
+            (Value::Float8(v), _, FLOAT4_OID) => Some(Value::Float4(v as f32)),
+            (Value::Float4(v), _, FLOAT8_OID) => Some(Value::Float8(v.into())),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a value encoded as `type_oid` can be bound to a parameter of type
+/// `param_type_oid`, i.e. whether they share the same binary representation.
+pub fn is_binary_compatible(type_oid: u32, param_type_oid: u32) -> bool {
+    let is_text = |oid| matches!(oid, TEXT_OID | VARCHAR_OID | BPCHAR_OID | NAME_OID);
+    type_oid == UNSPECIFIED_OID
+        || type_oid == param_type_oid
+        || (is_text(type_oid) && is_text(param_type_oid))
+}
+
+/// Formats the value as text. `NULL`s are formatted as an empty string, timestamps in
+/// ISO 8601, and `timestamptz`s are always in UTC.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Int2(v) => write!(f, "{}", v),
+            Value::Int4(v) => write!(f, "{}", v),
+            Value::Int8(v) => write!(f, "{}", v),
+            Value::Float4(v) => write!(f, "{}", v),
+            Value::Float8(v) => write!(f, "{}", v),
+            Value::Numeric(v) => write!(f, "{}", v),
+            Value::Text(v) => write!(f, "{}", v),
+            Value::Bytea(v) => {
+                write!(f, "\\x")?;
+                v.iter().try_for_each(|b| write!(f, "{:02x}", b))
+            }
+            Value::Date(v) => write!(f, "{}", v.format("%Y-%m-%d")),
+            Value::Timestamp(v) => write!(f, "{}", v.format("%Y-%m-%dT%H:%M:%S%.6f")),
+            Value::TimestampTz(v) => {
+                let v = DateTime::<Utc>::from_naive_utc_and_offset(*v, Utc);
+                write!(f, "{}", v.format("%Y-%m-%dT%H:%M:%S%.6f%:z"))
+            }
+        }
+    }
+}
+
+/// Values that can be sent as query parameters.
+pub trait ToSql: fmt::Debug + Sync {
+    /// OID of the type the value is encoded as. `0` lets the server infer it.
+    fn type_oid(&self) -> u32;
+
+    /// Encodes the value in binary format. `None` stands for `NULL`.
+    fn to_sql(&self) -> Option<Vec<u8>>;
+}
+
+macro_rules! impl_to_sql_be_bytes {
+    ($ty:ty, $oid:expr) => {
+        impl ToSql for $ty {
+            fn type_oid(&self) -> u32 {
+                $oid
+            }
+
+            fn to_sql(&self) -> Option<Vec<u8>> {
+                Some(self.to_be_bytes().to_vec())
+            }
+        }
+    };
+}
+
+impl_to_sql_be_bytes!(i16, INT2_OID);
+impl_to_sql_be_bytes!(i32, INT4_OID);
+impl_to_sql_be_bytes!(i64, INT8_OID);
+impl_to_sql_be_bytes!(f32, FLOAT4_OID);
+impl_to_sql_be_bytes!(f64, FLOAT8_OID);
+
+impl ToSql for bool {
+    fn type_oid(&self) -> u32 {
+        BOOL_OID
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        Some(vec![*self as u8])
+    }
+}
+
+impl ToSql for &str {
+    fn type_oid(&self) -> u32 {
+        TEXT_OID
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        Some(self.as_bytes().to_vec())
+    }
+}
+
+impl ToSql for String {
+    fn type_oid(&self) -> u32 {
+        TEXT_OID
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl ToSql for &[u8] {
+    fn type_oid(&self) -> u32 {
+        BYTEA_OID
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        Some(self.to_vec())
+    }
+}
+
+impl ToSql for Vec<u8> {
+    fn type_oid(&self) -> u32 {
+        BYTEA_OID
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        self.as_slice().to_sql()
+    }
+}
+
+impl ToSql for NaiveDate {
+    fn type_oid(&self) -> u32 {
+        DATE_OID
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        let days: i32 = (*self - pg_epoch().date()).num_days().try_into().unwrap();
+        days.to_sql()
+    }
+}
+
+impl ToSql for NaiveDateTime {
+    fn type_oid(&self) -> u32 {
+        TIMESTAMP_OID
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        (*self - pg_epoch()).num_microseconds().unwrap().to_sql()
+    }
+}
+
+impl ToSql for DateTime<Utc> {
+    fn type_oid(&self) -> u32 {
+        TIMESTAMPTZ_OID
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        self.naive_utc().to_sql()
+    }
+}
+
+/// `None`s are sent as `NULL`s of unspecified type.
+impl<T: ToSql> ToSql for Option<T> {
+    fn type_oid(&self) -> u32 {
+        self.as_ref().map_or(UNSPECIFIED_OID, |v| v.type_oid())
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        self.as_ref().and_then(|v| v.to_sql())
+    }
+}
+
+impl ToSql for Value {
+    fn type_oid(&self) -> u32 {
+        match self {
+            Value::Null => UNSPECIFIED_OID,
+            Value::Bool(v) => v.type_oid(),
+            Value::Int2(v) => v.type_oid(),
+            Value::Int4(v) => v.type_oid(),
+            Value::Int8(v) => v.type_oid(),
+            Value::Float4(v) => v.type_oid(),
+            Value::Float8(v) => v.type_oid(),
+            Value::Numeric(_) => NUMERIC_OID,
+            Value::Text(v) => v.type_oid(),
+            Value::Bytea(v) => v.type_oid(),
+            Value::Date(v) => v.type_oid(),
+            Value::Timestamp(v) => v.type_oid(),
+            Value::TimestampTz(_) => TIMESTAMPTZ_OID,
+        }
+    }
+
+    fn to_sql(&self) -> Option<Vec<u8>> {
+        match self {
+            Value::Null => None,
+            Value::Bool(v) => v.to_sql(),
+            Value::Int2(v) => v.to_sql(),
+            Value::Int4(v) => v.to_sql(),
+            Value::Int8(v) => v.to_sql(),
+            Value::Float4(v) => v.to_sql(),
+            Value::Float8(v) => v.to_sql(),
+            Value::Numeric(v) => Some(v.encode()),
+            Value::Text(v) => v.to_sql(),
+            Value::Bytea(v) => v.to_sql(),
+            Value::Date(v) => v.to_sql(),
+            Value::Timestamp(v) | Value::TimestampTz(v) => v.to_sql(),
+        }
+    }
+}
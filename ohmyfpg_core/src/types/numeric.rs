@@ -0,0 +1,105 @@
+use std::fmt;
+
+// Reference: https://github.com/postgres/postgres/blob/REL_15_STABLE/src/backend/utils/adt/numeric.c
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+/// Exact decimal as sent by the server: base 10000 digits, the first one being
+/// multiplied by 10000^`weight`, and `dscale` digits after the decimal point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Numeric {
+    weight: i16,
+    sign: u16,
+    dscale: u16,
+    digits: Vec<i16>,
+}
+
+impl Numeric {
+    /// Decodes the binary representation, or returns `None` if it's malformed.
+    pub fn decode(raw: &[u8]) -> Option<Numeric> {
+        let field = |i: usize| raw.get(i..i + 2).map(|b| [b[0], b[1]]);
+        let ndigits = i16::from_be_bytes(field(0)?);
+        let weight = i16::from_be_bytes(field(2)?);
+        let sign = u16::from_be_bytes(field(4)?);
+        let dscale = u16::from_be_bytes(field(6)?);
+        let digits = (0..usize::try_from(ndigits).ok()?)
+            .map(|i| field(8 + 2 * i).map(i16::from_be_bytes))
+            .collect::<Option<Vec<i16>>>()?;
+        match sign {
+            NUMERIC_POS | NUMERIC_NEG | NUMERIC_NAN | NUMERIC_PINF | NUMERIC_NINF => {
+                Some(Numeric {
+                    weight,
+                    sign,
+                    dscale,
+                    digits,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let ndigits = self.digits.len() as i16;
+        let mut raw = [
+            ndigits.to_be_bytes(),
+            self.weight.to_be_bytes(),
+            self.sign.to_be_bytes(),
+            self.dscale.to_be_bytes(),
+        ]
+        .concat();
+        for digit in self.digits.iter() {
+            raw.extend_from_slice(&digit.to_be_bytes());
+        }
+        raw
+    }
+
+    /// Whether it's neither `NaN` nor an infinity.
+    pub fn is_finite(&self) -> bool {
+        matches!(self.sign, NUMERIC_POS | NUMERIC_NEG)
+    }
+
+    fn digit(&self, i: i32) -> i16 {
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| self.digits.get(i))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// Formats the decimal like Postgres does, with exactly `dscale` digits after the
+/// decimal point.
+impl fmt::Display for Numeric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.sign {
+            NUMERIC_NAN => return write!(f, "NaN"),
+            NUMERIC_PINF => return write!(f, "Infinity"),
+            NUMERIC_NINF => return write!(f, "-Infinity"),
+            NUMERIC_NEG => write!(f, "-")?,
+            _ => {}
+        }
+        let weight = i32::from(self.weight);
+        if weight < 0 {
+            write!(f, "0")?;
+        } else {
+            write!(f, "{}", self.digit(0))?;
+            for i in 1..=weight {
+                write!(f, "{:04}", self.digit(i))?;
+            }
+        }
+        if self.dscale > 0 {
+            let mut fraction = String::new();
+            let mut i = weight + 1;
+            while fraction.len() < usize::from(self.dscale) {
+                fraction.push_str(&format!("{:04}", self.digit(i)));
+                i += 1;
+            }
+            fraction.truncate(usize::from(self.dscale));
+            write!(f, ".{}", fraction)?;
+        }
+        Ok(())
+    }
+}
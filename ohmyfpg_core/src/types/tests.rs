@@ -0,0 +1,109 @@
+use crate::client::FetchError;
+use crate::server::PgType;
+use crate::types::{Numeric, ToSql, Value};
+use chrono::NaiveDate;
+
+#[test]
+fn test_decode_format() {
+    let int4 = PgType::new(23, "int4".to_owned(), Some(4));
+    let v = Value::decode(&int4, Some(&[0, 0, 1, 0])).unwrap();
+    assert_eq!(v, Value::Int4(256));
+    assert_eq!(v.to_string(), "256");
+    assert_eq!(Value::decode(&int4, None).unwrap(), Value::Null);
+
+    let float8 = PgType::new(701, "float8".to_owned(), Some(8));
+    let v = Value::decode(&float8, Some(&1.5f64.to_be_bytes())).unwrap();
+    assert_eq!(v.to_string(), "1.5");
+
+    // 2022-10-01 12:30:00.5 is 717942600500000 microseconds after 2000-01-01
+    let timestamp = PgType::new(1114, "timestamp".to_owned(), Some(8));
+    let v = Value::decode(&timestamp, Some(&717942600500000i64.to_be_bytes())).unwrap();
+    assert_eq!(v.to_string(), "2022-10-01T12:30:00.500000");
+
+    let timestamptz = PgType::new(1184, "timestamptz".to_owned(), Some(8));
+    let v = Value::decode(&timestamptz, Some(&717942600500000i64.to_be_bytes())).unwrap();
+    assert_eq!(v.to_string(), "2022-10-01T12:30:00.500000+00:00");
+
+    let date = PgType::new(1082, "date".to_owned(), Some(4));
+    let v = Value::decode(&date, Some(&(-1i32).to_be_bytes())).unwrap();
+    assert_eq!(v.to_string(), "1999-12-31");
+
+    let jsonb = PgType::new(3802, "jsonb".to_owned(), None);
+    for raw in [Some(&b"{}"[..]), None] {
+        assert!(matches!(
+            Value::decode(&jsonb, raw),
+            Err(FetchError::UnsupportedTypeError(_))
+        ));
+    }
+}
+
+/// Encodes a numeric as `ndigits`, `weight`, `sign`, `dscale` and the base 10000 digits.
+fn numeric_bytes(weight: i16, sign: u16, dscale: u16, digits: &[i16]) -> Vec<u8> {
+    let mut raw = [
+        (digits.len() as i16).to_be_bytes(),
+        weight.to_be_bytes(),
+        sign.to_be_bytes(),
+        dscale.to_be_bytes(),
+    ]
+    .concat();
+    for digit in digits {
+        raw.extend_from_slice(&digit.to_be_bytes());
+    }
+    raw
+}
+
+#[test]
+fn test_decode_numeric() {
+    let numeric = PgType::new(1700, "numeric".to_owned(), None);
+    for (raw, expected) in [
+        (numeric_bytes(1, 0, 4, &[1, 2345, 6789]), "12345.6789"),
+        (numeric_bytes(1, 0, 0, &[1]), "10000"),
+        (numeric_bytes(0, 0, 2, &[100]), "100.00"),
+        (numeric_bytes(-1, 0x4000, 4, &[12]), "-0.0012"),
+        (numeric_bytes(-2, 0, 8, &[1234]), "0.00001234"),
+        (numeric_bytes(0, 0, 3, &[]), "0.000"),
+        (numeric_bytes(0, 0xC000, 0, &[]), "NaN"),
+        (numeric_bytes(0, 0xD000, 0, &[]), "Infinity"),
+        (numeric_bytes(0, 0xF000, 0, &[]), "-Infinity"),
+    ] {
+        let v = Value::decode(&numeric, Some(&raw)).unwrap();
+        assert_eq!(v.to_string(), expected);
+        // Sent back as is
+        assert_eq!(v.to_sql(), Some(raw));
+    }
+    assert_eq!(Numeric::decode(&[0, 1, 0, 0, 0, 0, 0, 0]), None);
+    assert!(matches!(
+        Value::decode(&numeric, Some(&[0, 0])),
+        Err(FetchError::InvalidStateError(_))
+    ));
+}
+
+#[test]
+fn test_to_sql() {
+    assert_eq!(256i32.type_oid(), 23);
+    assert_eq!(256i32.to_sql(), Some(vec![0, 0, 1, 0]));
+    assert_eq!("ab".to_sql(), Some(vec![97, 98]));
+    assert_eq!(None::<i64>.type_oid(), 0);
+    assert_eq!(None::<i64>.to_sql(), None);
+
+    let date = NaiveDate::from_ymd_opt(1999, 12, 31).unwrap();
+    assert_eq!(date.to_sql(), Some((-1i32).to_be_bytes().to_vec()));
+    let ts = date.and_hms_opt(23, 59, 59).unwrap();
+    assert_eq!(ts.to_sql(), Some((-1_000_000i64).to_be_bytes().to_vec()));
+    assert_eq!(Value::TimestampTz(ts).type_oid(), 1184);
+}
+
+#[test]
+fn test_cast() {
+    assert_eq!(Value::Int8(7).cast(23), Some(Value::Int4(7)));
+    assert_eq!(Value::Int8(1 << 40).cast(23), None);
+    assert_eq!(Value::Int8(1).cast(701), Some(Value::Float8(1.0)));
+    assert_eq!(Value::Float8(0.5).cast(700), Some(Value::Float4(0.5)));
+    // `text` and `varchar` share the same binary representation
+    assert_eq!(
+        Value::Text("a".to_owned()).cast(1043),
+        Some(Value::Text("a".to_owned()))
+    );
+    assert_eq!(Value::Null.cast(23), Some(Value::Null));
+    assert_eq!(Value::Bool(true).cast(23), None);
+}
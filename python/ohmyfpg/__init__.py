@@ -2,14 +2,183 @@ __NAME__ = "ohmyfpg"
 __VERSION__ = "0.4.0-dev.4"
 __DESCRIPTION__ = "Oh My Fast Postgres!"
 
+import asyncio
 import sys
+from typing import TYPE_CHECKING
+from typing import Any
+from typing import AsyncIterator
 from typing import Dict
+from typing import List
+from typing import NamedTuple
+from typing import Optional
+from typing import Sequence
+from typing import Tuple
 
 import numpy as np
 
 from ohmyfpg import ohmyfpg
 
 
+if TYPE_CHECKING:
+    import polars as pl
+
+AuthenticationError = ohmyfpg.PyAuthenticationError
+
+
+class SimpleQueryResult(NamedTuple):
+    """Result of one of the statements of a simple query.
+
+    `columns` is `None` for statements that don't return rows, and `command` is
+    `None` for empty statements. Values are returned as text.
+    """
+
+    columns: Optional[List[str]]
+    rows: List[List[Optional[str]]]
+    command: Optional[str]
+    row_count: Optional[int]
+
+
+class Cursor(object):
+    """Server-side cursor fetching a few rows at a time."""
+
+    def __init__(self, obj):
+        """Wrap the provided cursor."""
+        self._wrapped_obj = obj
+
+    async def fetch(self, max_rows: int) -> Optional[Dict[str, np.ndarray]]:
+        """Return the next `max_rows` rows as `numpy` columns.
+
+        Return `None` once all the rows have been fetched.
+        """
+        res = await _cancellable(self._wrapped_obj.fetch(max_rows))
+        if res is None:
+            return None
+
+        return _to_numpy_columns(res)
+
+    async def close(self):
+        """Close the cursor."""
+        await _cancellable(self._wrapped_obj.close())
+
+    async def __aenter__(self) -> "Cursor":
+        """Return the cursor itself."""
+        return self
+
+    async def __aexit__(self, *args):
+        """Close the cursor."""
+        await self.close()
+
+
+class PreparedStatement(object):
+    """Prepared statement that can be executed any number of times."""
+
+    def __init__(self, obj):
+        """Wrap the provided prepared statement."""
+        self._wrapped_obj = obj
+
+    @property
+    def param_types(self) -> List[Optional[str]]:
+        """Return the names of the parameters types.
+
+        Unsupported types are reported as `None`.
+        """
+        return self._wrapped_obj.param_types
+
+    @property
+    def columns(self) -> List[Tuple[str, Optional[str]]]:
+        """Return the names and the types names of the result columns.
+
+        Unsupported types are reported as `None`.
+        """
+        return self._wrapped_obj.columns
+
+    async def fetch(self, params: Sequence[Any] = ()) -> Dict[str, np.ndarray]:
+        """Return the result of the statement as `numpy` columns.
+
+        Parameters are validated and converted to the types of the statement
+        parameters before being sent, raising `ValueError` when they don't fit.
+        """
+        res = await _cancellable(self._wrapped_obj.fetch(list(params)))
+        return _to_numpy_columns(res)
+
+    async def close(self):
+        """Close the prepared statement."""
+        await _cancellable(self._wrapped_obj.close())
+
+    async def __aenter__(self) -> "PreparedStatement":
+        """Return the prepared statement itself."""
+        return self
+
+    async def __aexit__(self, *args):
+        """Close the prepared statement."""
+        await self.close()
+
+
+class Transaction(object):
+    """Transaction, or savepoint when nested, used as an async context manager.
+
+    It's committed on exit, or rolled back if an exception was raised.
+    """
+
+    def __init__(
+        self,
+        obj,
+        isolation_level: Optional[str] = None,
+        read_only: bool = False,
+        deferrable: bool = False,
+        nested: bool = False,
+    ):
+        """Wrap the provided connection, without starting the transaction yet."""
+        self._wrapped_obj = obj
+        self._options = (isolation_level, read_only, deferrable)
+        self._nested = nested
+        self._savepoint: Optional[str] = None
+        self._done = False
+
+    async def start(self) -> "Transaction":
+        """Start the transaction, or create the savepoint."""
+        if self._nested:
+            self._savepoint = await _cancellable(self._wrapped_obj.savepoint())
+        else:
+            await _cancellable(self._wrapped_obj.begin(*self._options))
+        return self
+
+    def savepoint(self) -> "Transaction":
+        """Return a nested transaction backed by a savepoint."""
+        return Transaction(self._wrapped_obj, nested=True)
+
+    async def commit(self):
+        """Commit the transaction, or release the savepoint."""
+        if self._savepoint is not None:
+            await _cancellable(self._wrapped_obj.release_savepoint(self._savepoint))
+        else:
+            await _cancellable(self._wrapped_obj.commit())
+        self._done = True
+
+    async def rollback(self):
+        """Roll back the transaction, or roll back to the savepoint."""
+        if self._savepoint is not None:
+            await _cancellable(
+                self._wrapped_obj.rollback_to_savepoint(self._savepoint)
+            )
+        else:
+            await _cancellable(self._wrapped_obj.rollback())
+        self._done = True
+
+    async def __aenter__(self) -> "Transaction":
+        """Start the transaction."""
+        return await self.start()
+
+    async def __aexit__(self, exc_type, *args):
+        """Commit the transaction, or roll it back on errors."""
+        if self._done:
+            return
+        if exc_type is None:
+            await self.commit()
+        else:
+            await self.rollback()
+
+
 class Connection(object):
     """Wrapper connection object."""
 
@@ -23,20 +192,288 @@ class Connection(object):
             return getattr(self, attr)
         return getattr(self._wrapped_obj, attr)
 
-    async def fetch(self, query_string: str) -> Dict[str, np.ndarray]:
-        """Return the result of the query as `numpy` columns."""
-        res = await self._wrapped_obj.fetch(query_string)
-        d = {}
-        for k, v in res.items():
-            arr = np.frombuffer(v[0], dtype=np.dtype(v[1]))
-            if sys.byteorder == 'little':
-                arr = arr.byteswap().newbyteorder()
+    async def cancel(self):
+        """Cancel the query being run, if any, e.g. from another task.
+
+        The cancelled query raises an error. Cancelling the task awaiting a query
+        cancels the query as well.
+        """
+        await self._wrapped_obj.cancel()
+
+    async def fetch(
+        self, query_string: str, params: Sequence[Any] = ()
+    ) -> Dict[str, np.ndarray]:
+        """Return the result of the query as `numpy` columns.
+
+        Parameters are referenced in the query as `$1`, `$2`, etc. Supported types
+        are `None`, `bool`, `int`, `float`, `str`, `bytes`, `datetime.date` and
+        `datetime.datetime`.
+        """
+        res = await _cancellable(self._wrapped_obj.fetch(query_string, list(params)))
+        return _to_numpy_columns(res)
+
+    def transaction(
+        self,
+        isolation_level: Optional[str] = None,
+        read_only: bool = False,
+        deferrable: bool = False,
+    ) -> Transaction:
+        """Return a transaction to be used with `async with`.
+
+        `isolation_level` is one of `read_uncommitted`, `read_committed`,
+        `repeatable_read` and `serializable`, the server default when `None`.
+        """
+        return Transaction(self._wrapped_obj, isolation_level, read_only, deferrable)
+
+    async def fetch_pipeline(
+        self, queries: Sequence[Tuple[str, Sequence[Any]]]
+    ) -> List[Dict[str, np.ndarray]]:
+        """Return the results of the `(query, params)` pairs as `numpy` columns, sending
+        all the queries at once to save the round trips.
+
+        Queries run in a single transaction: if one fails, the error is raised and none
+        of them takes effect.
+        """
+        res = await _cancellable(
+            self._wrapped_obj.fetch_pipeline(
+                [(query_string, list(params)) for query_string, params in queries]
+            )
+        )
+        return [_to_numpy_columns(r) for r in res]
+
+    async def fetch_copy(self, query_string: str) -> Dict[str, np.ndarray]:
+        """Return the result of the query as `numpy` columns, transferred with a binary
+        `COPY TO STDOUT`.
+
+        This is usually faster than `fetch` for large results, but parameters aren't
+        supported.
+        """
+        res = await _cancellable(self._wrapped_obj.fetch_copy(query_string))
+        return _to_numpy_columns(res)
+
+    async def execute(
+        self, query_string: str, params: Sequence[Any] = ()
+    ) -> Tuple[str, Optional[int]]:
+        """Run a statement that doesn't return rows, such as an `INSERT` or a DDL.
+
+        Return the command and the number of affected rows, e.g. `("INSERT", 3)`.
+        The number of rows is `None` for commands that don't report it.
+        """
+        return await _cancellable(self._wrapped_obj.execute(query_string, list(params)))
+
+    async def copy_from_arrays(self, table: str, columns: Dict[str, np.ndarray]) -> int:
+        """Insert the `numpy` columns into the table with a binary `COPY`.
+
+        The dtype of each column must match the type of the table column, e.g.
+        `int32` for `int4`. Return the number of rows inserted.
+        """
+        arrays = []
+        for name, arr in columns.items():
+            arr = np.ascontiguousarray(arr, dtype=arr.dtype.newbyteorder(">"))
+            arrays.append((name, arr.tobytes(), arr.dtype.str))
+
+        return await _cancellable(self._wrapped_obj.copy_from_arrays(table, arrays))
+
+    async def simple_query(self, query_string: str) -> List[SimpleQueryResult]:
+        """Run a script of one or more statements separated by semicolons.
+
+        Return the result of each statement. Parameters are not supported.
+        """
+        res = await _cancellable(self._wrapped_obj.simple_query(query_string))
+        return [SimpleQueryResult(*r) for r in res]
+
+    async def prepare(self, query_string: str) -> PreparedStatement:
+        """Prepare the query as a named statement.
+
+        The types of the parameters and of the result columns are available before
+        executing it.
+        """
+        return PreparedStatement(
+            await _cancellable(self._wrapped_obj.prepare(query_string))
+        )
+
+    async def cursor(self, query_string: str) -> Cursor:
+        """Open a server-side cursor on the query.
+
+        The cursor lives inside a transaction that is committed once all the open
+        cursors are closed, or rolled back if the last one is garbage collected
+        without being closed.
+        """
+        return Cursor(await _cancellable(self._wrapped_obj.cursor(query_string)))
+
+    async def fetch_batches(
+        self,
+        query_string: str,
+        batch_rows: int,
+    ) -> AsyncIterator[Dict[str, np.ndarray]]:
+        """Iterate over the result of the query in batches of `numpy` columns.
+
+        Each batch has at most `batch_rows` rows and is decoded as soon as its rows
+        are received. The connection can't be used for other queries until the
+        iteration is over. When breaking out of the loop early, close the iterator,
+        e.g. with `contextlib.aclosing`, to release the connection right away: the
+        rows left are then skipped by the next query.
+        """
+        batches = self._wrapped_obj.fetch_batches(query_string, batch_rows)
+        try:
+            async for res in batches:
+                yield _to_numpy_columns(res)
+        finally:
+            await batches.aclose()
+
+    async def fetch_polars(self, query_string: str) -> "pl.DataFrame":
+        """Return the result of the query as a `polars` DataFrame.
+
+        The DataFrame is built directly from the `numpy` columns without going
+        through `pandas`. Requires the `polars` extra to be installed.
+        """
+        import polars as pl
+
+        return pl.DataFrame(await self.fetch(query_string))
+
+    async def fetch_to_parquet(
+        self,
+        query_string: str,
+        path: str,
+        row_group_size: int = 1024 * 1024,
+        compression: str = "snappy",
+    ) -> int:
+        """Stream the result of the query into a parquet file at `path`.
+
+        Rows are written in row groups of `row_group_size` rows, so that the whole
+        result is never kept in memory, and `ValueError` is raised when it's 0.
+        `compression` can be one of `"uncompressed"`, `"snappy"` and `"zstd"`.
+        Return the number of rows written.
+        """
+        return await _cancellable(
+            self._wrapped_obj.fetch_to_parquet(
+                query_string,
+                path,
+                row_group_size,
+                compression,
+            )
+        )
+
+    async def fetch_to_csv(
+        self,
+        query_string: str,
+        path: str,
+        delimiter: str = ",",
+        quoting: str = "necessary",
+        header: bool = True,
+        null: str = "",
+    ) -> int:
+        """Stream the result of the query into a CSV file at `path`.
+
+        `quoting` can be one of `"always"`, `"necessary"`, `"non_numeric"` and
+        `"never"`, and `null` is the text written in place of `NULL`s. Return the
+        number of rows written.
+        """
+        return await _cancellable(
+            self._wrapped_obj.fetch_to_csv(
+                query_string,
+                path,
+                delimiter,
+                quoting,
+                header,
+                null,
+            )
+        )
+
+    async def fetch_to_ndjson(self, query_string: str, path: str) -> int:
+        """Stream the result of the query into a newline-delimited JSON file.
+
+        Return the number of rows written.
+        """
+        return await _cancellable(self._wrapped_obj.fetch_to_ndjson(query_string, path))
+
+
+async def _cancellable(query):
+    """Await the result of a query, cancelling it when the task is cancelled.
+
+    The query is cancelled server-side if it's running, or else not run at all,
+    while its future is shielded from the task cancellation, so that the connection
+    is ready for the next query once the server stopped it.
+    """
+    fut = asyncio.ensure_future(query)
+    try:
+        return await asyncio.shield(fut)
+    except asyncio.CancelledError:
+        try:
+            await query.cancel()
+            await asyncio.shield(fut)
+        except Exception:
+            # The query fails once cancelled
+            pass
+        raise
+
+
+def _to_numpy_columns(res) -> Dict[str, np.ndarray]:
+    d = {}
+    for k, v in res.items():
+        arr = np.frombuffer(v[0], dtype=np.dtype(v[1]))
+        if sys.byteorder == 'little':
+            arr = arr.byteswap().newbyteorder()
+
+        d[k] = arr
+
+    return d
+
+
+async def connect(
+    dsn: str = '',
+    statement_cache_size: int = 100,
+    connect_timeout: Optional[float] = None,
+    query_timeout: Optional[float] = None,
+    keepalives_idle: Optional[int] = None,
+    keepalives_interval: Optional[int] = None,
+    keepalives_count: Optional[int] = None,
+) -> Connection:
+    """Connect to the given `dsn`.
+
+    Like `libpq`, options missing from the `dsn` are taken from the `service`
+    file entry and the `PG*` environment variables, and the password from
+    `~/.pgpass`.
+
+    Up to `statement_cache_size` prepared statements are cached by query text, so
+    that running the same query again skips parsing it. `0` disables the cache.
 
-            d[k] = arr
+    The timeouts and TCP keepalive settings are in seconds, and override those of
+    the `dsn`. `TimeoutError` is raised when connecting to a host takes longer than
+    `connect_timeout`, or when the response of a query takes longer than
+    `query_timeout`, and the query is then cancelled. The time spent by the caller
+    between the batches of `fetch_batches` is counted too.
+    """
+    params = [
+        (name, str(value))
+        for name, value in [
+            ('connect_timeout', connect_timeout),
+            ('query_timeout', query_timeout),
+            ('keepalives_idle', keepalives_idle),
+            ('keepalives_interval', keepalives_interval),
+            ('keepalives_count', keepalives_count),
+        ]
+        if value is not None
+    ]
+    return Connection(await ohmyfpg.connect(dsn, statement_cache_size, params))
 
-        return d
 
+async def fetch_partitioned(
+    dsn: str,
+    query_string: str,
+    partition_on: str,
+    partitions: int,
+    bounds: Optional[Tuple[int, int]] = None,
+) -> Dict[str, np.ndarray]:
+    """Return the result of the query as `numpy` columns, fetched concurrently over
+    `partitions` connections.
 
-async def connect(dsn: str) -> Connection:
-    """Connect to the given `dsn`."""
-    return Connection(await ohmyfpg.connect(dsn))
+    The query is split into ranges of the integer column `partition_on`, between the
+    inclusive `bounds`, computed with `min` and `max` when not given. Each range is
+    fetched with its own connection to `dsn`, and the columns are concatenated.
+    Cancelling the task cancels the queries of all the connections.
+    """
+    res = await _cancellable(
+        ohmyfpg.fetch_partitioned(dsn, query_string, partition_on, partitions, bounds)
+    )
+    return _to_numpy_columns(res)
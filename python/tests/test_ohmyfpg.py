@@ -1,5 +1,54 @@
+import asyncio
+
+import pytest
+
 import ohmyfpg
 
 
+class _BlockedQuery(object):
+    """Query blocked on the server until it's cancelled."""
+
+    def __init__(self):
+        self.cancelled = False
+        self._fut = asyncio.get_running_loop().create_future()
+
+    def __await__(self):
+        return self._fut.__await__()
+
+    async def cancel(self):
+        self.cancelled = True
+        self._fut.set_exception(Exception('canceling statement due to user request'))
+
+
+class _BlockedConnection(object):
+    """Connection whose `prepare` and `cursor` queries are blocked."""
+
+    def __init__(self):
+        self.query = None
+
+    def prepare(self, query_string):
+        self.query = _BlockedQuery()
+        return self.query
+
+    def cursor(self, query_string):
+        self.query = _BlockedQuery()
+        return self.query
+
+
 def test_description():
     assert ohmyfpg.__DESCRIPTION__ == 'Oh My Fast Postgres!'
+
+
+@pytest.mark.parametrize('method', ['prepare', 'cursor'])
+def test_cancel_task(method):
+    async def run():
+        wrapped = _BlockedConnection()
+        conn = ohmyfpg.Connection(wrapped)
+        task = asyncio.ensure_future(getattr(conn, method)('SELECT pg_sleep(10)'))
+        await asyncio.sleep(0)
+        task.cancel()
+        with pytest.raises(asyncio.CancelledError):
+            await task
+        assert wrapped.query.cancelled
+
+    asyncio.run(run())